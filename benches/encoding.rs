@@ -0,0 +1,101 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Encoding & decoding throughput benchmarks. Run with `cargo bench` (add
+//! `--features bitcoin` to include bitcoin transaction benchmarks).
+
+#[macro_use]
+extern crate criterion;
+
+use std::collections::BTreeMap;
+
+use criterion::{black_box, Criterion, Throughput};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+fn bench_roundtrip<T>(c: &mut Criterion, name: &str, data: &T)
+where
+    T: StrictEncode + StrictDecode,
+{
+    let bytes = data.strict_serialize().unwrap();
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("encode", |b| {
+        b.iter(|| black_box(data).strict_serialize().unwrap())
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| T::strict_deserialize(black_box(&bytes)).unwrap())
+    });
+    group.finish();
+}
+
+fn vec_u64(c: &mut Criterion) {
+    let data: Vec<u64> = (0..10_000u64).map(|i| i * 0x1_0001).collect();
+    bench_roundtrip(c, "Vec<u64> 10k", &data);
+}
+
+fn btree_map(c: &mut Criterion) {
+    let data: BTreeMap<u32, u32> =
+        (0..10_000u32).map(|i| (i * 7, i ^ 0xdead_beef)).collect();
+    bench_roundtrip(c, "BTreeMap<u32, u32> 10k", &data);
+}
+
+#[cfg(feature = "derive")]
+include!("../derive/examples/common/opcode.rs");
+
+/// Decoding of a large enum with sparse variant values. The derived `match`
+/// over the variant values is lowered by the compiler into a jump table or a
+/// binary search, so no custom lookup is generated by the derive macro
+#[cfg(feature = "derive")]
+fn sparse_enum(c: &mut Criterion) {
+    let data: Vec<Opcode> =
+        Opcode::ALL.iter().copied().cycle().take(10_000).collect();
+    bench_roundtrip(c, "Vec<Opcode> 10k", &data);
+}
+
+#[cfg(not(feature = "derive"))]
+fn sparse_enum(_: &mut Criterion) {}
+
+#[cfg(feature = "bitcoin")]
+fn transaction(c: &mut Criterion) {
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, Script, Transaction, TxIn, TxOut, Txid};
+
+    let input = (0..500u32)
+        .map(|vout| TxIn {
+            previous_output: OutPoint::new(Txid::from_inner([0xA5; 32]), vout),
+            script_sig: Script::from(vec![0x51; 107]),
+            sequence: 0xFFFF_FFFE,
+            witness: vec![vec![0x30; 72], vec![0x02; 33]],
+        })
+        .collect();
+    let output = (0..500u64)
+        .map(|value| TxOut {
+            value: value * 1000,
+            script_pubkey: Script::from(vec![0x00; 34]),
+        })
+        .collect();
+    let tx = Transaction {
+        version: 2,
+        lock_time: 0,
+        input,
+        output,
+    };
+    bench_roundtrip(c, "Transaction 500x500", &tx);
+}
+
+#[cfg(not(feature = "bitcoin"))]
+fn transaction(_: &mut Criterion) {}
+
+criterion_group!(benches, vec_u64, btree_map, sparse_enum, transaction);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+// LNP/BP client-side-validation library implementing respective LNPBP
+// specifications & standards (LNPBP-7, 8, 9, 42)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+// Large enum with sparse discriminant values. Shared between the derive example
+// and the benchmarks with `include!`; the macro generates the list of all
+// variants from the same definition, so the two can't diverge.
+
+macro_rules! opcode_enum {
+    (
+        $( #[$attr:meta] )*
+        $vis:vis enum $ty:ident {
+            $( $variant:ident = $value:literal ),+ $(,)?
+        }
+    ) => {
+        $( #[$attr] )*
+        $vis enum $ty {
+            $( $variant = $value ),+
+        }
+
+        impl $ty {
+            /// All enum variants in the order of their declaration
+            pub const ALL: &'static [$ty] = &[ $( $ty::$variant ),+ ];
+        }
+    };
+}
+
+opcode_enum! {
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(by_value)]
+    #[repr(u8)]
+    pub enum Opcode {
+        Op00 = 0x00,
+        Op4F = 0x4f,
+        Op51 = 0x51,
+        Op54 = 0x54,
+        Op57 = 0x57,
+        Op5A = 0x5a,
+        Op5D = 0x5d,
+        Op60 = 0x60,
+        Op63 = 0x63,
+        Op66 = 0x66,
+        Op69 = 0x69,
+        Op6C = 0x6c,
+        Op6F = 0x6f,
+        Op72 = 0x72,
+        Op75 = 0x75,
+        Op78 = 0x78,
+        Op7B = 0x7b,
+        Op7E = 0x7e,
+        Op81 = 0x81,
+        Op84 = 0x84,
+        Op87 = 0x87,
+        Op8A = 0x8a,
+        Op8D = 0x8d,
+        Op90 = 0x90,
+        Op93 = 0x93,
+        Op96 = 0x96,
+        Op99 = 0x99,
+        Op9C = 0x9c,
+        Op9F = 0x9f,
+        OpA2 = 0xa2,
+        OpA5 = 0xa5,
+        OpA8 = 0xa8,
+        OpAB = 0xab,
+        OpAE = 0xae,
+        OpB1 = 0xb1,
+        OpB4 = 0xb4,
+        OpB7 = 0xb7,
+        OpBA = 0xba,
+        OpBD = 0xbd,
+        OpC0 = 0xc0,
+        OpC3 = 0xc3,
+        OpC6 = 0xc6,
+        OpC9 = 0xc9,
+        OpCC = 0xcc,
+        OpCF = 0xcf,
+        OpD2 = 0xd2,
+        OpD5 = 0xd5,
+        OpD8 = 0xd8,
+        OpDB = 0xdb,
+        OpDE = 0xde,
+    }
+}
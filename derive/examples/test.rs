@@ -17,7 +17,7 @@
 #[macro_use]
 extern crate amplify_derive;
 
-use strict_encoding::{StrictDecode, StrictEncode};
+use strict_encoding::{StrictDecode, StrictEncode, StrictSize};
 
 #[derive(StrictEncode, StrictDecode)]
 struct Me(u8);
@@ -96,7 +96,7 @@ enum CustomValues {
     Bit64 = 8,
 }
 
-#[derive(StrictEncode, StrictDecode)]
+#[derive(PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
 #[strict_encoding(by_order, repr = u16)]
 #[repr(u16)]
 enum U16 {
@@ -116,6 +116,45 @@ struct Skipping {
     pub ephemeral: Option<bool>,
 }
 
+/// Stand-in for `Instant`-like values, which can't be meaningfully serialized
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+struct Timestamp(u64);
+
+#[derive(PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct Cached {
+    pub id: u16,
+
+    #[strict_encoding(skip)]
+    pub computed_at: Timestamp,
+
+    pub name: String,
+}
+
+#[derive(PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+enum Gapped {
+    #[strict_encoding(value = 0x01)]
+    Ping,
+
+    #[strict_encoding(value = 0x10)]
+    Data(u8),
+
+    #[strict_encoding(value = 0xFF)]
+    Close,
+}
+
+#[derive(PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct Evolving {
+    pub id: u16,
+
+    // Field added in a later version: data produced by the previous versions
+    // do not contain it and will be decoded with `0` value
+    #[strict_encoding(default)]
+    pub flags: u8,
+
+    #[strict_encoding(default)]
+    pub note: Vec<u8>,
+}
+
 #[derive(StrictEncode, StrictDecode)]
 enum CustomErr<Err>
 where
@@ -124,6 +163,324 @@ where
     Other(Err),
 }
 
+// Same enum with variants reordered in the source code
+#[derive(Clone, Copy, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+#[strict_encoding(by_order)]
+#[repr(u8)]
+enum ByOrderReordered {
+    Bit64 = 8,
+    Bit32 = 4,
+    Bit16 = 2,
+    Bit8 = 1,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+#[strict_encoding(by_value)]
+#[repr(u8)]
+enum ByValueReordered {
+    Bit64 = 8,
+    Bit32 = 4,
+    Bit16 = 2,
+    Bit8 = 1,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct Generic<T>(T);
+
+/// Type which is used only as a phantom marker and can't be encoded
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Marker;
+
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+struct Phantom<T> {
+    pub id: u16,
+
+    #[strict_encoding(skip)]
+    pub marker: std::marker::PhantomData<T>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+#[strict_encoding(bound = "T: StrictEncode + StrictDecode + Copy")]
+struct CustomBound<T>
+where
+    T: Copy,
+{
+    pub value: T,
+}
+
+#[derive(
+    Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode, StrictSize,
+)]
+struct Header {
+    pub version: u8,
+    pub flags: u16,
+    pub timestamp: u64,
+    #[strict_encoding(skip)]
+    pub received: u32,
+}
+
+#[derive(
+    Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode, StrictSize,
+)]
+#[strict_encoding(by_order, repr = u16)]
+enum Message {
+    Ping,
+    Header(Header),
+    Range { from: u32, to: u32 },
+}
+
+#[derive(
+    Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode, StrictSize,
+)]
+#[strict_encoding(variant_guard)]
+enum Transfer {
+    Inbound(u32),
+    Outbound { amount: u32 },
+}
+
+/// Facade re-exporting strict encoding library under a different name
+mod facade {
+    pub use strict_encoding as encoding;
+}
+
+mod aliased {
+    // Derived implementations refer to the library through the facade path
+    #[derive(
+        Clone,
+        PartialEq,
+        Eq,
+        Debug,
+        crate::facade::encoding::StrictEncode,
+        crate::facade::encoding::StrictDecode,
+    )]
+    #[strict_encoding(crate = crate::facade::encoding)]
+    pub struct Record {
+        pub id: u32,
+        pub tag: Option<u8>,
+    }
+}
+
+#[derive(Clone, Copy, StrictEncode, StrictDecode)]
+#[strict_encoding(size = 4)]
+#[repr(C)]
+union RawWord {
+    bytes: [u8; 4],
+    halves: [[u8; 2]; 2],
+}
+
+mod guard_v1 {
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(variant_guard)]
+    pub enum Proto {
+        Init(u8),
+        Close,
+    }
+}
+
+mod guard_v2 {
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(variant_guard)]
+    pub enum Proto {
+        Close,
+        Init(u8),
+    }
+}
+
+mod guard_v3 {
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(variant_guard)]
+    pub enum Proto {
+        Start(u8),
+        Close,
+    }
+}
+
+include!("common/opcode.rs");
+
 fn main() {
-    assert_eq!(ByValue::Bit64.strict_serialize().unwrap(), vec![8])
+    assert_eq!(ByValue::Bit64.strict_serialize().unwrap(), vec![8]);
+
+    // Encoding of `by_order` enums changes with variant reordering, while
+    // `by_value` enums keep it stable
+    assert_eq!(ByOrder::Bit64.strict_serialize().unwrap(), vec![3]);
+    assert_eq!(ByOrderReordered::Bit64.strict_serialize().unwrap(), vec![0]);
+    assert_eq!(
+        ByValueReordered::Bit64.strict_serialize().unwrap(),
+        ByValue::Bit64.strict_serialize().unwrap()
+    );
+    for byte in 0u8..=8 {
+        assert_eq!(
+            ByValueReordered::strict_deserialize(&[byte])
+                .ok()
+                .map(|v| v as u8),
+            ByValue::strict_deserialize(&[byte]).ok().map(|v| v as u8)
+        );
+    }
+
+    assert_eq!(U16::Bit32.strict_serialize().unwrap(), vec![0x02, 0x00]);
+    assert_eq!(U16::strict_deserialize(&[0x03, 0x00]), Ok(U16::Bit64));
+    assert_eq!(
+        U16::strict_deserialize(&[0x00, 0x01]),
+        Err(strict_encoding::Error::EnumValueNotKnown("U16", 0x100))
+    );
+    assert!(U16::strict_deserialize(&[0x00]).is_err());
+
+    assert_eq!(Gapped::Ping.strict_serialize().unwrap(), vec![0x01]);
+    assert_eq!(
+        Gapped::Data(7).strict_serialize().unwrap(),
+        vec![0x10, 0x07]
+    );
+    assert_eq!(Gapped::Close.strict_serialize().unwrap(), vec![0xFF]);
+    assert_eq!(
+        Gapped::strict_deserialize(&[0x10, 0x07]),
+        Ok(Gapped::Data(7))
+    );
+    assert_eq!(Gapped::strict_deserialize(&[0xFF]), Ok(Gapped::Close));
+    assert_eq!(
+        Gapped::strict_deserialize(&[0x00]),
+        Err(strict_encoding::Error::EnumValueNotKnown("Gapped", 0))
+    );
+
+    let record = aliased::Record {
+        id: 1,
+        tag: Some(2),
+    };
+    let ser = record.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x01, 0x00, 0x00, 0x00, 0x01, 0x02]);
+    assert_eq!(aliased::Record::strict_deserialize(&ser), Ok(record));
+
+    let word = RawWord {
+        halves: [[0x01, 0x02], [0x03, 0x04]],
+    };
+    let ser = word.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x01, 0x02, 0x03, 0x04]);
+    let word = RawWord::strict_deserialize(&ser).unwrap();
+    assert_eq!(unsafe { word.bytes }, [0x01, 0x02, 0x03, 0x04]);
+    assert!(RawWord::strict_deserialize(&[0x01, 0x02]).is_err());
+
+    let generic = Generic("text".to_string());
+    let ser = generic.strict_serialize().unwrap();
+    assert_eq!(Generic::<String>::strict_deserialize(&ser), Ok(generic));
+
+    let phantom = Phantom::<Marker> {
+        id: 3,
+        marker: Default::default(),
+    };
+    let ser = phantom.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x03, 0x00]);
+    assert_eq!(Phantom::<Marker>::strict_deserialize(&ser), Ok(phantom));
+
+    let custom = CustomBound { value: 7u8 };
+    let ser = custom.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x07]);
+    assert_eq!(CustomBound::strict_deserialize(&ser), Ok(custom));
+
+    let evolving = Evolving {
+        id: 5,
+        flags: 3,
+        note: vec![0xA1],
+    };
+    let ser = evolving.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x05, 0x00, 0x03, 0x01, 0x00, 0xA1]);
+    assert_eq!(Evolving::strict_deserialize(&ser), Ok(evolving));
+    assert_eq!(
+        Evolving::strict_deserialize(&[0x05, 0x00]),
+        Ok(Evolving {
+            id: 5,
+            flags: 0,
+            note: vec![]
+        })
+    );
+    assert_eq!(
+        Evolving::strict_deserialize(&[0x05, 0x00, 0x03]),
+        Ok(Evolving {
+            id: 5,
+            flags: 3,
+            note: vec![]
+        })
+    );
+    // Field truncated partway through is not defaulted
+    assert_eq!(
+        Evolving::strict_deserialize(&[0x05, 0x00, 0x03, 0x03, 0x00]),
+        Err(strict_encoding::Error::UnexpectedEof)
+    );
+    assert_eq!(
+        Evolving::strict_deserialize(&[0x05, 0x00, 0x03, 0x01]),
+        Err(strict_encoding::Error::UnexpectedEof)
+    );
+
+    let cached = Cached {
+        id: 0x0201,
+        computed_at: Timestamp(1_600_000_000),
+        name: "a".to_string(),
+    };
+    let ser = cached.strict_serialize().unwrap();
+    assert_eq!(ser, vec![0x01, 0x02, 0x01, 0x00, b'a']);
+    assert_eq!(
+        Cached::strict_deserialize(&ser),
+        Ok(Cached {
+            computed_at: Timestamp::default(),
+            ..cached
+        })
+    );
+
+    let ser = guard_v1::Proto::Init(5).strict_serialize().unwrap();
+    assert_eq!(ser.len(), 4);
+    assert_eq!(&ser[2..], &[0, 5]);
+    assert_eq!(
+        guard_v1::Proto::strict_deserialize(&ser),
+        Ok(guard_v1::Proto::Init(5))
+    );
+    // Reordered variants must not decode old data
+    assert!(matches!(
+        guard_v2::Proto::strict_deserialize(&ser),
+        Err(strict_encoding::Error::DataIntegrityError(_))
+    ));
+    // Renamed variants must not decode old data
+    assert!(matches!(
+        guard_v3::Proto::strict_deserialize(&ser),
+        Err(strict_encoding::Error::DataIntegrityError(_))
+    ));
+
+    assert_eq!(Opcode::ALL.len(), 50);
+    for op in Opcode::ALL {
+        let ser = op.strict_serialize().unwrap();
+        assert_eq!(ser, vec![*op as u8]);
+        assert_eq!(Opcode::strict_deserialize(&ser), Ok(*op));
+    }
+    assert!(matches!(
+        Opcode::strict_deserialize(&[0x01]),
+        Err(strict_encoding::Error::EnumValueNotKnown("Opcode", 1))
+    ));
+
+    let header = Header {
+        version: 1,
+        flags: 0x0102,
+        timestamp: 0x0304_0506,
+        received: 0,
+    };
+    assert_eq!(header.strict_size(), 11);
+    assert_eq!(
+        header.strict_size(),
+        header.strict_serialize().unwrap().len()
+    );
+    for msg in &[
+        Message::Ping,
+        Message::Header(header.clone()),
+        Message::Range { from: 0, to: 100 },
+    ] {
+        assert_eq!(msg.strict_size(), msg.strict_serialize().unwrap().len());
+    }
+    assert_eq!(Message::Header(header).strict_size(), 13);
+
+    // Fixed size is known only if it does not depend on the value
+    assert_eq!(Header::LEN, Some(11));
+    assert_eq!(Message::LEN, None);
+    assert_eq!(Transfer::LEN, Some(1 + 2 + 4));
+    let buf = [0u8; Transfer::LEN.unwrap()];
+    assert_eq!(
+        Transfer::Inbound(5).strict_serialize().unwrap().len(),
+        buf.len()
+    );
+    assert_eq!(Transfer::Outbound { amount: 5 }.strict_size(), buf.len());
 }
@@ -16,22 +16,24 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, TokenStreamExt};
 use syn::spanned::Spanned;
 use syn::{
-    Data, DataEnum, DataStruct, DeriveInput, Error, Field, Fields, Ident,
-    ImplGenerics, Index, LitStr, Result, TypeGenerics, WhereClause,
+    Data, DataEnum, DataStruct, DeriveInput, Error, Expr, ExprLit, Field,
+    Fields, Ident, ImplGenerics, Index, Lit, LitStr, Path, Result,
+    TypeGenerics, WhereClause,
 };
 
 use amplify::proc_attr::ParametrizedAttr;
 
-use crate::param::EncodingDerive;
+use crate::param::{
+    bounded_generics, variant_guard, EncodingDerive, UnionDerive,
+};
 use crate::ATTR_NAME;
 
 pub(crate) fn decode_derive(input: DeriveInput) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) =
-        input.generics.split_for_impl();
+    let mut global_param = ParametrizedAttr::with(ATTR_NAME, &input.attrs)?;
+    let generics = bounded_generics(&input, &mut global_param, "StrictDecode")?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let ident_name = &input.ident;
 
-    let global_param = ParametrizedAttr::with(ATTR_NAME, &input.attrs)?;
-
     match input.data {
         Data::Struct(data) => decode_struct_impl(
             data,
@@ -50,13 +52,41 @@ pub(crate) fn decode_derive(input: DeriveInput) -> Result<TokenStream2> {
             where_clause,
         ),
         //strict_encode_inner_enum(&input, &data),
-        Data::Union(_) => Err(Error::new_spanned(
-            &input,
-            "Deriving StrictDecode is not supported in unions",
-        )),
+        Data::Union(_) if !input.generics.params.is_empty() => {
+            Err(Error::new_spanned(
+                &input.generics,
+                "Deriving StrictDecode is not supported in generic unions",
+            ))
+        }
+        Data::Union(_) => decode_union_impl(ident_name, global_param),
     }
 }
 
+fn decode_union_impl(
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+) -> Result<TokenStream2> {
+    let encoding = UnionDerive::try_from(&mut global_param)?;
+    let import = encoding.use_crate;
+    let size = encoding.size;
+
+    Ok(quote! {
+        #[allow(unused_qualifications)]
+        impl #import::StrictDecode for #ident_name {
+            #[inline]
+            fn strict_decode<D: ::std::io::Read>(mut d: D) -> Result<Self, #import::Error> {
+                const _: [(); #size] = [(); ::core::mem::size_of::<#ident_name>()];
+                let mut buf = [0u8; #size];
+                d.read_exact(&mut buf)?;
+                // Safety: the buffer has the same size as the union, as
+                // guaranteed by the size check above; the user guarantees
+                // that any byte string is a valid union value
+                Ok(unsafe { ::core::ptr::read_unaligned(buf.as_ptr() as *const Self) })
+            }
+        }
+    })
+}
+
 fn decode_struct_impl(
     data: DataStruct,
     ident_name: &Ident,
@@ -67,18 +97,18 @@ fn decode_struct_impl(
 ) -> Result<TokenStream2> {
     let encoding = EncodingDerive::try_from(&mut global_param, true, false)?;
 
+    let import = encoding.use_crate;
+
     let inner_impl = match data.fields {
         Fields::Named(ref fields) => {
-            decode_fields_impl(&fields.named, global_param, false)?
+            decode_fields_impl(&fields.named, global_param, &import, false)?
         }
         Fields::Unnamed(ref fields) => {
-            decode_fields_impl(&fields.unnamed, global_param, false)?
+            decode_fields_impl(&fields.unnamed, global_param, &import, false)?
         }
         Fields::Unit => quote! {},
     };
 
-    let import = encoding.use_crate;
-
     Ok(quote! {
         #[allow(unused_qualifications)]
         impl #impl_generics #import::StrictDecode for #ident_name #ty_generics #where_clause {
@@ -101,10 +131,42 @@ fn decode_enum_impl(
 ) -> Result<TokenStream2> {
     let encoding = EncodingDerive::try_from(&mut global_param, true, true)?;
     let repr = encoding.repr;
+    let import = &encoding.use_crate;
+    let enum_name = LitStr::new(&ident_name.to_string(), Span::call_site());
+
+    let guard_impl = if encoding.variant_guard {
+        let guard = variant_guard(&data.variants);
+        quote! {
+            let guard = u16::strict_decode(&mut d)?;
+            if guard != #guard {
+                return Err(#import::Error::DataIntegrityError(format!(
+                    "variant guard mismatch for enum `{}`: expected {:#06x}, got {:#06x}",
+                    #enum_name, #guard, guard
+                )));
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
 
     let mut inner_impl = TokenStream2::new();
+    let mut known_values = Vec::with_capacity(data.variants.len());
+    let mut discriminant = Some(0u128);
 
     for (order, variant) in data.variants.iter().enumerate() {
+        // Tracking the numeric value of the variant discriminant, if it is
+        // known at the compile time
+        if let Some((_, expr)) = &variant.discriminant {
+            discriminant = match expr {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }) => lit.base10_parse().ok(),
+                _ => None,
+            };
+        }
+        let variant_discriminant = discriminant;
+        discriminant = discriminant.and_then(|d| d.checked_add(1));
+
         let mut local_param =
             ParametrizedAttr::with(ATTR_NAME, &variant.attrs)?;
 
@@ -114,6 +176,7 @@ fn decode_enum_impl(
         let mut combined = global_param.clone().merged(local_param.clone())?;
         combined.args.remove("repr");
         combined.args.remove("crate");
+        combined.args.remove("variant_guard");
         let encoding = EncodingDerive::try_from(&mut combined, false, true)?;
 
         if encoding.skip {
@@ -122,21 +185,32 @@ fn decode_enum_impl(
 
         let field_impl = match variant.fields {
             Fields::Named(ref fields) => {
-                decode_fields_impl(&fields.named, local_param, true)?
+                decode_fields_impl(&fields.named, local_param, import, true)?
             }
             Fields::Unnamed(ref fields) => {
-                decode_fields_impl(&fields.unnamed, local_param, true)?
+                decode_fields_impl(&fields.unnamed, local_param, import, true)?
             }
             Fields::Unit => TokenStream2::new(),
         };
 
         let ident = &variant.ident;
-        let value = match (encoding.value, encoding.by_order) {
-            (Some(val), _) => val.to_token_stream(),
-            (None, true) => Index::from(order as usize).to_token_stream(),
-            (None, false) => quote! { Self::#ident as #repr },
+        let (value, known_value) = match (encoding.value, encoding.by_order) {
+            (Some(val), _) => {
+                let known = val.base10_parse().ok();
+                (val.to_token_stream(), known)
+            }
+            (None, true) => {
+                (Index::from(order).to_token_stream(), Some(order as u128))
+            }
+            (None, false) => {
+                (quote! { Self::#ident as #repr }, variant_discriminant)
+            }
         };
 
+        if let Some(known) = known_value {
+            known_values.push((known, value.clone()));
+        }
+
         inner_impl.append_all(quote_spanned! { variant.span() =>
             x if x == #value => {
                 Self::#ident {
@@ -146,14 +220,24 @@ fn decode_enum_impl(
         });
     }
 
-    let import = encoding.use_crate;
-    let enum_name = LitStr::new(&ident_name.to_string(), Span::call_site());
+    // Values known at the compile time must not repeat, otherwise some of the
+    // variants will never be decoded
+    known_values.sort_by_key(|(known, _)| *known);
+    if let Some(pair) =
+        known_values.windows(2).find(|pair| pair[0].0 == pair[1].0)
+    {
+        return Err(Error::new_spanned(
+            &pair[1].1,
+            "duplicated strict encoding value for enum variant",
+        ));
+    }
 
     Ok(quote! {
         #[allow(unused_qualifications)]
         impl #impl_generics #import::StrictDecode for #ident_name #ty_generics #where_clause {
             fn strict_decode<D: ::std::io::Read>(mut d: D) -> Result<Self, #import::Error> {
                 use #import::StrictDecode;
+                #guard_impl
                 Ok(match #repr::strict_decode(&mut d)? {
                     #inner_impl
                     unknown => Err(#import::Error::EnumValueNotKnown(#enum_name, unknown as usize))?
@@ -166,14 +250,15 @@ fn decode_enum_impl(
 fn decode_fields_impl<'a>(
     fields: impl IntoIterator<Item = &'a Field>,
     mut parent_param: ParametrizedAttr,
+    import: &Path,
     is_enum: bool,
 ) -> Result<TokenStream2> {
     let mut stream = TokenStream2::new();
 
     parent_param.args.remove("crate");
-    let parent_attr =
+    let _ =
         EncodingDerive::try_from(&mut parent_param.clone(), false, is_enum)?;
-    let import = parent_attr.use_crate;
+    let mut defaulted = false;
 
     for (index, field) in fields.into_iter().enumerate() {
         let mut local_param = ParametrizedAttr::with(ATTR_NAME, &field.attrs)?;
@@ -191,9 +276,36 @@ fn decode_fields_impl<'a>(
             .unwrap_or_else(|| Index::from(index).to_token_stream());
 
         if encoding.skip {
-            stream.append_all(quote_spanned! { field.span() =>
-                #name: Default::default(),
+            // Spanning to the field type makes compiler to report missing
+            // `Default` implementation right at the skipped field
+            let ty = &field.ty;
+            stream.append_all(quote_spanned! { ty.span() =>
+                #name: <#ty as ::core::default::Default>::default(),
+            });
+        } else if encoding.default {
+            defaulted = true;
+            let ty = &field.ty;
+            // Default value is used only if the data stream ends right
+            // before the field; a partially present field is an error
+            stream.append_all(quote_spanned! { ty.span() =>
+                #name: {
+                    let mut reader = #import::StrictReader::new(&mut d);
+                    match #import::StrictDecode::strict_decode(&mut reader) {
+                        Err(#import::Error::UnexpectedEof)
+                            if reader.position() == 0 =>
+                        {
+                            <#ty as ::core::default::Default>::default()
+                        }
+                        res => res?,
+                    }
+                },
             });
+        } else if defaulted {
+            return Err(Error::new(
+                field.span(),
+                "fields with `default` attribute must not be followed by \
+                 other fields which are not defaulted or skipped",
+            ));
         } else {
             stream.append_all(quote_spanned! { field.span() =>
                 #name: #import::StrictDecode::strict_decode(&mut d)?,
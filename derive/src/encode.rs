@@ -12,26 +12,30 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{ToTokens, TokenStreamExt};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::TokenStreamExt;
 use syn::spanned::Spanned;
 use syn::{
-    Data, DataEnum, DataStruct, DeriveInput, Error, Field, Fields, Ident,
-    ImplGenerics, Index, Result, TypeGenerics, WhereClause,
+    Data, DataEnum, DataStruct, DeriveInput, Error, Ident, ImplGenerics,
+    Result, TypeGenerics, WhereClause,
 };
 
 use amplify::proc_attr::ParametrizedAttr;
 
-use crate::param::EncodingDerive;
+use crate::fields::{
+    encoded_fields, encoded_variants, EncodedField, EncodedVariant,
+};
+use crate::param::{
+    bounded_generics, variant_guard, EncodingDerive, UnionDerive,
+};
 use crate::ATTR_NAME;
 
 pub(crate) fn encode_derive(input: DeriveInput) -> Result<TokenStream2> {
-    let (impl_generics, ty_generics, where_clause) =
-        input.generics.split_for_impl();
+    let mut global_param = ParametrizedAttr::with(ATTR_NAME, &input.attrs)?;
+    let generics = bounded_generics(&input, &mut global_param, "StrictEncode")?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let ident_name = &input.ident;
 
-    let global_param = ParametrizedAttr::with(ATTR_NAME, &input.attrs)?;
-
     match input.data {
         Data::Struct(data) => encode_struct_impl(
             data,
@@ -50,13 +54,43 @@ pub(crate) fn encode_derive(input: DeriveInput) -> Result<TokenStream2> {
             where_clause,
         ),
         //strict_encode_inner_enum(&input, &data),
-        Data::Union(_) => Err(Error::new_spanned(
-            &input,
-            "Deriving StrictEncode is not supported in unions",
-        )),
+        Data::Union(_) if !input.generics.params.is_empty() => {
+            Err(Error::new_spanned(
+                &input.generics,
+                "Deriving StrictEncode is not supported in generic unions",
+            ))
+        }
+        Data::Union(_) => encode_union_impl(ident_name, global_param),
     }
 }
 
+fn encode_union_impl(
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+) -> Result<TokenStream2> {
+    let encoding = UnionDerive::try_from(&mut global_param)?;
+    let import = encoding.use_crate;
+    let size = encoding.size;
+
+    Ok(quote! {
+        #[allow(unused_qualifications)]
+        impl #import::StrictEncode for #ident_name {
+            #[inline]
+            fn strict_encode<E: ::std::io::Write>(&self, mut e: E) -> Result<usize, #import::Error> {
+                const _: [(); #size] = [(); ::core::mem::size_of::<#ident_name>()];
+                // Safety: the slice covers exactly the memory of the union
+                // value, as guaranteed by the size check above; the user
+                // guarantees that all of these bytes are initialized
+                let data = unsafe {
+                    ::core::slice::from_raw_parts(self as *const Self as *const u8, #size)
+                };
+                e.write_all(data)?;
+                Ok(#size)
+            }
+        }
+    })
+}
+
 fn encode_struct_impl(
     data: DataStruct,
     ident_name: &Ident,
@@ -67,15 +101,8 @@ fn encode_struct_impl(
 ) -> Result<TokenStream2> {
     let encoding = EncodingDerive::try_from(&mut global_param, true, false)?;
 
-    let inner_impl = match data.fields {
-        Fields::Named(ref fields) => {
-            encode_fields_impl(&fields.named, global_param, false)?
-        }
-        Fields::Unnamed(ref fields) => {
-            encode_fields_impl(&fields.unnamed, global_param, false)?
-        }
-        Fields::Unit => quote! { Ok(0) },
-    };
+    let fields = encoded_fields(&data.fields, global_param, false)?;
+    let inner_impl = encode_fields_impl(&fields);
 
     let import = encoding.use_crate;
 
@@ -104,64 +131,28 @@ fn encode_enum_impl(
     let encoding = EncodingDerive::try_from(&mut global_param, true, true)?;
     let repr = encoding.repr;
 
-    let mut inner_impl = TokenStream2::new();
-
-    for (order, variant) in data.variants.iter().enumerate() {
-        let mut local_param =
-            ParametrizedAttr::with(ATTR_NAME, &variant.attrs)?;
-
-        // First, test individual attribute
-        let _ = EncodingDerive::try_from(&mut local_param, false, true)?;
-        // Second, combine global and local together
-        let mut combined = global_param.clone().merged(local_param.clone())?;
-        combined.args.remove("repr");
-        combined.args.remove("crate");
-        let encoding = EncodingDerive::try_from(&mut combined, false, true)?;
-
-        if encoding.skip {
-            continue;
-        }
+    let guard_impl = if encoding.variant_guard {
+        let guard = variant_guard(&data.variants);
+        quote! { len += #guard.strict_encode(&mut e)?; }
+    } else {
+        TokenStream2::new()
+    };
 
-        let captures = variant
-            .fields
-            .iter()
-            .enumerate()
-            .map(|(i, f)| {
-                f.ident.as_ref().map(Ident::to_token_stream).unwrap_or_else(
-                    || {
-                        Ident::new(&format!("_{}", i), Span::call_site())
-                            .to_token_stream()
-                    },
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let (field_impl, bra_captures_ket) = match variant.fields {
-            Fields::Named(ref fields) => (
-                encode_fields_impl(&fields.named, local_param, true)?,
-                quote! { { #( #captures ),* } },
-            ),
-            Fields::Unnamed(ref fields) => (
-                encode_fields_impl(&fields.unnamed, local_param, true)?,
-                quote! { ( #( #captures ),* ) },
-            ),
-            Fields::Unit => (TokenStream2::new(), TokenStream2::new()),
-        };
-
-        let captures = match captures.len() {
-            0 => quote! {},
-            _ => quote! { let data = ( #( #captures ),* , ); },
-        };
+    let mut inner_impl = TokenStream2::new();
 
+    for encoded in encoded_variants(&data, &global_param)? {
+        let EncodedVariant {
+            variant,
+            value,
+            pattern,
+            captures,
+            fields,
+        } = encoded;
         let ident = &variant.ident;
-        let value = match (encoding.value, encoding.by_order) {
-            (Some(val), _) => val.to_token_stream(),
-            (None, true) => Index::from(order as usize).to_token_stream(),
-            (None, false) => quote! { Self::#ident },
-        };
+        let field_impl = encode_fields_impl(&fields);
 
         inner_impl.append_all(quote_spanned! { variant.span() =>
-            Self::#ident #bra_captures_ket => {
+            Self::#ident #pattern => {
                 len += (#value as #repr).strict_encode(&mut e)?;
                 #captures
                 #field_impl
@@ -178,6 +169,7 @@ fn encode_enum_impl(
             fn strict_encode<E: ::std::io::Write>(&self, mut e: E) -> Result<usize, #import::Error> {
                 use #import::StrictEncode;
                 let mut len = 0;
+                #guard_impl
                 match self {
                     #inner_impl
                 }
@@ -187,41 +179,14 @@ fn encode_enum_impl(
     })
 }
 
-fn encode_fields_impl<'a>(
-    fields: impl IntoIterator<Item = &'a Field>,
-    parent_param: ParametrizedAttr,
-    is_enum: bool,
-) -> Result<TokenStream2> {
+fn encode_fields_impl(fields: &[EncodedField]) -> TokenStream2 {
     let mut stream = TokenStream2::new();
 
-    for (index, field) in fields.into_iter().enumerate() {
-        let mut local_param = ParametrizedAttr::with(ATTR_NAME, &field.attrs)?;
-
-        // First, test individual attribute
-        let _ = EncodingDerive::try_from(&mut local_param, false, is_enum)?;
-        // Second, combine global and local together
-        let mut combined = parent_param.clone().merged(local_param)?;
-        combined.args.remove("crate");
-        let encoding = EncodingDerive::try_from(&mut combined, false, is_enum)?;
-
-        if encoding.skip {
-            continue;
-        }
-
-        let index = Index::from(index).to_token_stream();
-        let name = if is_enum {
-            index
-        } else {
-            field
-                .ident
-                .as_ref()
-                .map(Ident::to_token_stream)
-                .unwrap_or(index)
-        };
+    for EncodedField { field, member } in fields {
         stream.append_all(quote_spanned! { field.span() =>
-            len += data.#name.strict_encode(&mut e)?;
+            len += data.#member.strict_encode(&mut e)?;
         })
     }
 
-    Ok(stream)
+    stream
 }
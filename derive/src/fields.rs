@@ -0,0 +1,160 @@
+// LNP/BP client-side-validation library implementing respective LNPBP
+// specifications & standards (LNPBP-7, 8, 9, 42)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Traversal of the enum variants and fields which participate in the strict
+//! encoding, shared by all derivation macros working with encoded values
+//! (`StrictEncode` and `StrictSize`), such that they always agree on which
+//! parts of the data are encoded.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{DataEnum, Field, Fields, Ident, Index, Result, Variant};
+
+use amplify::proc_attr::ParametrizedAttr;
+
+use crate::param::EncodingDerive;
+use crate::ATTR_NAME;
+
+/// Enum variant which is not skipped from the strict encoding
+pub(crate) struct EncodedVariant<'a> {
+    pub variant: &'a Variant,
+    /// Value of the variant representation in the encoded data
+    pub value: TokenStream2,
+    /// Pattern matching the variant and capturing all of its fields, like
+    /// `{ a, b }` or `(_0, _1)`; empty for unit variants
+    pub pattern: TokenStream2,
+    /// Statement binding the captured fields to `data` tuple, such that they
+    /// can be accessed by the indexes of [`EncodedVariant::fields`]
+    pub captures: TokenStream2,
+    /// Fields of the variant which are not skipped
+    pub fields: Vec<EncodedField<'a>>,
+}
+
+/// Structure or enum variant field which is not skipped from the strict
+/// encoding
+pub(crate) struct EncodedField<'a> {
+    pub field: &'a Field,
+    /// Member by which the field is accessed from `data` variable
+    pub member: TokenStream2,
+}
+
+/// Collects all enum variants which are not skipped from the strict
+/// encoding, in the order of their declaration
+pub(crate) fn encoded_variants<'a>(
+    data: &'a DataEnum,
+    global_param: &ParametrizedAttr,
+) -> Result<Vec<EncodedVariant<'a>>> {
+    let mut variants = Vec::with_capacity(data.variants.len());
+
+    for (order, variant) in data.variants.iter().enumerate() {
+        let mut local_param =
+            ParametrizedAttr::with(ATTR_NAME, &variant.attrs)?;
+
+        // First, test individual attribute
+        let _ = EncodingDerive::try_from(&mut local_param, false, true)?;
+        // Second, combine global and local together
+        let mut combined = global_param.clone().merged(local_param.clone())?;
+        combined.args.remove("repr");
+        combined.args.remove("crate");
+        combined.args.remove("variant_guard");
+        let encoding = EncodingDerive::try_from(&mut combined, false, true)?;
+
+        if encoding.skip {
+            continue;
+        }
+
+        let ident = &variant.ident;
+        let value = match (&encoding.value, encoding.by_order) {
+            (Some(val), _) => val.to_token_stream(),
+            (None, true) => Index::from(order).to_token_stream(),
+            (None, false) => quote! { Self::#ident },
+        };
+
+        let captures = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                f.ident.as_ref().map(Ident::to_token_stream).unwrap_or_else(
+                    || {
+                        Ident::new(&format!("_{}", i), Span::call_site())
+                            .to_token_stream()
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let pattern = match variant.fields {
+            Fields::Named(_) => quote! { { #( #captures ),* } },
+            Fields::Unnamed(_) => quote! { ( #( #captures ),* ) },
+            Fields::Unit => TokenStream2::new(),
+        };
+        let fields = encoded_fields(&variant.fields, local_param, true)?;
+        let captures = match captures.len() {
+            0 => quote! {},
+            _ => quote! { let data = ( #( #captures ),* , ); },
+        };
+
+        variants.push(EncodedVariant {
+            variant,
+            value,
+            pattern,
+            captures,
+            fields,
+        });
+    }
+
+    Ok(variants)
+}
+
+/// Collects all fields of a structure or enum variant which are not skipped
+/// from the strict encoding, in the order of their declaration
+pub(crate) fn encoded_fields<'a>(
+    fields: &'a Fields,
+    parent_param: ParametrizedAttr,
+    is_enum: bool,
+) -> Result<Vec<EncodedField<'a>>> {
+    let mut encoded = Vec::with_capacity(fields.len());
+
+    for (index, field) in fields.iter().enumerate() {
+        let mut local_param = ParametrizedAttr::with(ATTR_NAME, &field.attrs)?;
+
+        // First, test individual attribute
+        let _ = EncodingDerive::try_from(&mut local_param, false, is_enum)?;
+        // Second, combine global and local together
+        let mut combined = parent_param.clone().merged(local_param)?;
+        combined.args.remove("crate");
+        let encoding = EncodingDerive::try_from(&mut combined, false, is_enum)?;
+
+        if encoding.skip {
+            continue;
+        }
+
+        // Enum variant fields are captured into a tuple, so they are always
+        // accessed by index
+        let index = Index::from(index).to_token_stream();
+        let member = if is_enum {
+            index
+        } else {
+            field
+                .ident
+                .as_ref()
+                .map(Ident::to_token_stream)
+                .unwrap_or(index)
+        };
+        encoded.push(EncodedField { field, member });
+    }
+
+    Ok(encoded)
+}
@@ -25,6 +25,11 @@
 //! `#[derive(`[`StrictDecode`]`)]`, which can be added on top of any structure
 //! you'd like to support string encoding (see Example section below).
 //!
+//! Additionally, `#[derive(`[`StrictSize`]`)]` computes the size of the
+//! encoded data without performing the actual encoding; it uses the same
+//! `#[strict_encoding(...)]` attribute and must be derived together with
+//! [`StrictEncode`].
+//!
 //! Encoding/decoding implemented by both of these macros may be configured at
 //! type and individual field level using `#[strict_encoding(...)]` attribute
 //!
@@ -43,6 +48,13 @@
 //!
 //! Allows to specify custom path to `strict_encoding` crate
 //!
+//! ### `bound = "<where predicates>"`
+//!
+//! By default, derived implementations require each of the generic type
+//! parameters used by the fields which are not skipped to implement the
+//! derived trait. This argument replaces these requirements with the
+//! provided list of where predicates, like `bound = "T: StrictEncode + Copy"`.
+//!
 //! ### `repr = <uint>`
 //!
 //! Can be used with enum types only.
@@ -66,6 +78,30 @@
 //! If neither of these two arguments is provided, the macro defaults to
 //! `by_order` encoding.
 //!
+//! ### `variant_guard`
+//!
+//! Can be used with enum types only.
+//!
+//! Prefixes the enum encoding with a 2-byte hash of the ordered list of the
+//! enum variant names, which is verified during decoding. This ensures that
+//! data encoded with a version of the enum having variants renamed or
+//! reordered will fail to decode with `Error::DataIntegrityError` instead of
+//! being silently misinterpreted.
+//!
+//! ### `size = <unsigned integer>`
+//!
+//! Required for unions and can't be used with other types.
+//!
+//! Unions are encoded as a raw byte string of their in-memory representation
+//! with the length given by this argument, which must be equal to the size of
+//! the union type (otherwise the compiler will error). Since this requires
+//! unsafe code, the user is responsible for the union to have no padding
+//! bytes (i.e. all bytes of each of its fields are initialized) and for any
+//! byte string of the given length to be a valid value of each union field.
+//! Such encoding is platform-dependent for the fields with multi-byte
+//! integers, so `#[repr(C)]` unions of byte arrays are preferred. Generic
+//! unions are not supported.
+//!
 //!
 //! ## Attribute arguments at field and enum variant level
 //!
@@ -80,6 +116,18 @@
 //! Allowed only for named and unnamed (tuple) structure fields and enum variant
 //! associated value fields.
 //!
+//! ### `default`
+//!
+//! Initializes field value with `Default::default()` on type deserialization
+//! if the data end right before the field value (i.e. decoding of the field
+//! fails with `Error::UnexpectedEof` without consuming any data); the field is
+//! serialized as usual. Partially present field value is still an error. This
+//! allows adding new fields to the end of the structure while keeping
+//! ability to decode data produced before the field was introduced.
+//!
+//! Allowed only for the trailing fields: all fields following a field with
+//! `default` must also have either `default` or `skip` attribute.
+//!
 //! ### `value = <unsigned integer>`
 //!
 //! Allowed only for enum variants.
@@ -180,7 +228,9 @@ extern crate syn;
 
 mod decode;
 mod encode;
+mod fields;
 mod param;
+mod size;
 
 use proc_macro::TokenStream;
 use syn::DeriveInput;
@@ -204,3 +254,17 @@ pub fn derive_strict_decode(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+/// Derives [`::strict_encoding::StrictSize`] implementation for the type,
+/// which computes the size of the encoded data as a sum of the sizes of all
+/// fields which are not skipped, plus the size of the variant representation
+/// for enums. The derived `StrictSize::LEN` constant is known at compile time
+/// if all of these fields have fixed size (and, for enums, if all variants
+/// have the same size).
+#[proc_macro_derive(StrictSize, attributes(strict_encoding))]
+pub fn derive_strict_size(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    size::size_derive(derive_input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -12,9 +12,17 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use std::collections::HashSet;
 use std::convert::TryInto;
-use syn::{Error, Ident, LitInt, Path, Result};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    Data, DeriveInput, Error, Field, Generics, Ident, LitInt, LitStr, Path,
+    Result, Variant, WherePredicate,
+};
+
+use quote::ToTokens;
 
 use amplify::proc_attr::{
     ArgValue, ArgValueReq, AttrReq, LiteralClass, ParametrizedAttr, ValueClass,
@@ -24,9 +32,11 @@ use amplify::proc_attr::{
 pub(crate) struct EncodingDerive {
     pub use_crate: Path,
     pub skip: bool,
+    pub default: bool,
     pub by_order: bool,
     pub value: Option<LitInt>,
     pub repr: Ident,
+    pub variant_guard: bool,
 }
 
 impl EncodingDerive {
@@ -41,7 +51,8 @@ impl EncodingDerive {
             }
         } else {
             map! {
-                "skip" => ArgValueReq::Prohibited
+                "skip" => ArgValueReq::Prohibited,
+                "default" => ArgValueReq::Prohibited
             }
         };
 
@@ -50,6 +61,7 @@ impl EncodingDerive {
             map.insert("by_value", ArgValueReq::Prohibited);
             if is_global {
                 map.insert("repr", ArgValueReq::with_default(ident!(u8)));
+                map.insert("variant_guard", ArgValueReq::Prohibited);
             } else {
                 map.insert(
                     "value",
@@ -107,14 +119,160 @@ impl EncodingDerive {
 
         let skip = attr.args.get("skip").is_some();
 
+        let default = attr.args.contains_key("default");
+
         let by_order = !attr.args.contains_key("by_value");
 
+        let variant_guard = attr.args.contains_key("variant_guard");
+
         Ok(EncodingDerive {
             use_crate,
             skip,
+            default,
             by_order,
             value,
             repr,
+            variant_guard,
+        })
+    }
+}
+
+/// Parameters of strict encoding derivation for unions, which are encoded as
+/// a fixed-size raw byte string
+pub(crate) struct UnionDerive {
+    pub use_crate: Path,
+    pub size: LitInt,
+}
+
+impl UnionDerive {
+    pub(crate) fn try_from(attr: &mut ParametrizedAttr) -> Result<UnionDerive> {
+        attr.check(AttrReq::with(map! {
+            "crate" => ArgValueReq::with_default(ident!(strict_encoding)),
+            "size" => ArgValueReq::required(ValueClass::Literal(LiteralClass::Int))
+        }))?;
+
+        let use_crate = attr
+            .args
+            .get("crate")
+            .cloned()
+            .unwrap_or_else(|| ArgValue::from(ident!(strict_encoding)))
+            .try_into()
+            .expect("amplify_syn is broken: requirements for crate arg are not satisfied");
+
+        let size = attr
+            .args
+            .get("size")
+            .cloned()
+            .expect("amplify_syn is broken: size arg is required")
+            .try_into()
+            .expect("amplify_syn is broken: requirements for size arg are not satisfied");
+
+        Ok(UnionDerive { use_crate, size })
+    }
+}
+
+/// Computes 16-bit guard value for the enum from the ordered list of its
+/// variant names, using FNV-1a hash function folded to 16 bits.
+pub(crate) fn variant_guard(variants: &Punctuated<Variant, Comma>) -> u16 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for (no, variant) in variants.iter().enumerate() {
+        if no > 0 {
+            hash ^= b',' as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        for byte in variant.ident.to_string().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    (hash ^ (hash >> 16) ^ (hash >> 32) ^ (hash >> 48)) as u16
+}
+
+/// Constructs generics for the derived implementation of `trait_name` trait.
+///
+/// If the type has `bound` argument in its attribute, the where predicates
+/// from it are added to the type generics. Otherwise, the trait bound is
+/// added for each of the generic type parameters used by the fields, which
+/// are not skipped during the encoding.
+pub(crate) fn bounded_generics(
+    input: &DeriveInput,
+    global_param: &mut ParametrizedAttr,
+    trait_name: &str,
+) -> Result<Generics> {
+    let mut generics = input.generics.clone();
+
+    if let Some(bound) = global_param.args.remove("bound") {
+        let bound: LitStr = bound.try_into().map_err(|_| {
+            Error::new(
+                Span::call_site(),
+                "`bound` requires string literal with where predicates",
+            )
+        })?;
+        let predicates = bound.parse_with(
+            Punctuated::<WherePredicate, Comma>::parse_terminated,
+        )?;
+        generics.make_where_clause().predicates.extend(predicates);
+        return Ok(generics);
+    }
+
+    let mut fields = Vec::<&Field>::new();
+    match input.data {
+        Data::Struct(ref data) => fields.extend(&data.fields),
+        Data::Enum(ref data) => {
+            for variant in &data.variants {
+                if !is_skipped(&variant.attrs)? {
+                    fields.extend(&variant.fields);
+                }
+            }
+        }
+        Data::Union(_) => return Ok(generics),
+    }
+    let mut used = HashSet::new();
+    for field in fields {
+        if !is_skipped(&field.attrs)? {
+            collect_idents(field.ty.to_token_stream(), &mut used);
+        }
+    }
+
+    let use_crate: Path = global_param
+        .args
+        .get("crate")
+        .cloned()
+        .unwrap_or_else(|| ArgValue::from(ident!(strict_encoding)))
+        .try_into()
+        .map_err(|_| {
+            Error::new(Span::call_site(), "`crate` requires path to the crate")
+        })?;
+    let trait_name = Ident::new(trait_name, Span::call_site());
+    let predicates = generics
+        .type_params()
+        .filter(|param| used.contains(&param.ident))
+        .map(|param| {
+            let ident = &param.ident;
+            parse_quote! { #ident: #use_crate::#trait_name }
         })
+        .collect::<Vec<WherePredicate>>();
+    generics.make_where_clause().predicates.extend(predicates);
+
+    Ok(generics)
+}
+
+fn is_skipped(attrs: &[syn::Attribute]) -> Result<bool> {
+    // Arguments without values are kept in `paths` until the attribute is
+    // checked against requirements
+    let attr = ParametrizedAttr::with(crate::ATTR_NAME, attrs)?;
+    Ok(attr.args.contains_key("skip")
+        || attr.paths.iter().any(|path| path.is_ident("skip")))
+}
+
+fn collect_idents(tokens: TokenStream2, idents: &mut HashSet<Ident>) {
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident);
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+        }
     }
 }
@@ -0,0 +1,225 @@
+// LNP/BP client-side-validation library implementing respective LNPBP
+// specifications & standards (LNPBP-7, 8, 9, 42)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::TokenStreamExt;
+use syn::spanned::Spanned;
+use syn::{
+    Data, DataEnum, DataStruct, DeriveInput, Error, Ident, ImplGenerics, Path,
+    Result, TypeGenerics, WhereClause,
+};
+
+use amplify::proc_attr::ParametrizedAttr;
+
+use crate::fields::{
+    encoded_fields, encoded_variants, EncodedField, EncodedVariant,
+};
+use crate::param::{bounded_generics, EncodingDerive, UnionDerive};
+use crate::ATTR_NAME;
+
+pub(crate) fn size_derive(input: DeriveInput) -> Result<TokenStream2> {
+    let mut global_param = ParametrizedAttr::with(ATTR_NAME, &input.attrs)?;
+    let generics = bounded_generics(&input, &mut global_param, "StrictSize")?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident_name = &input.ident;
+
+    match input.data {
+        Data::Struct(data) => size_struct_impl(
+            data,
+            ident_name,
+            global_param,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        ),
+        Data::Enum(data) => size_enum_impl(
+            data,
+            ident_name,
+            global_param,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        ),
+        Data::Union(_) if !input.generics.params.is_empty() => {
+            Err(Error::new_spanned(
+                &input.generics,
+                "Deriving StrictSize is not supported in generic unions",
+            ))
+        }
+        Data::Union(_) => size_union_impl(ident_name, global_param),
+    }
+}
+
+fn size_union_impl(
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+) -> Result<TokenStream2> {
+    let encoding = UnionDerive::try_from(&mut global_param)?;
+    let import = encoding.use_crate;
+    let size = encoding.size;
+
+    Ok(quote! {
+        #[allow(unused_qualifications)]
+        impl #import::StrictSize for #ident_name {
+            const LEN: Option<usize> = Some(#size);
+
+            #[inline]
+            fn strict_size(&self) -> usize {
+                #size
+            }
+        }
+    })
+}
+
+fn size_struct_impl(
+    data: DataStruct,
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+    impl_generics: ImplGenerics,
+    ty_generics: TypeGenerics,
+    where_clause: Option<&WhereClause>,
+) -> Result<TokenStream2> {
+    let encoding = EncodingDerive::try_from(&mut global_param, true, false)?;
+    let import = encoding.use_crate;
+
+    let fields = encoded_fields(&data.fields, global_param, false)?;
+    let inner_impl = size_fields_impl(&fields);
+    let fixed_len = fixed_len_impl(&fields, &import);
+
+    Ok(quote! {
+        #[allow(unused_qualifications)]
+        impl #impl_generics #import::StrictSize for #ident_name #ty_generics #where_clause {
+            const LEN: Option<usize> = #fixed_len;
+
+            fn strict_size(&self) -> usize {
+                use #import::StrictSize;
+                let mut len = 0;
+                let data = self;
+                #inner_impl
+                len
+            }
+        }
+    })
+}
+
+fn size_enum_impl(
+    data: DataEnum,
+    ident_name: &Ident,
+    mut global_param: ParametrizedAttr,
+    impl_generics: ImplGenerics,
+    ty_generics: TypeGenerics,
+    where_clause: Option<&WhereClause>,
+) -> Result<TokenStream2> {
+    let encoding = EncodingDerive::try_from(&mut global_param, true, true)?;
+    let repr = encoding.repr;
+    let import = encoding.use_crate;
+
+    let header_len = if encoding.variant_guard {
+        quote! {
+            ::core::mem::size_of::<#repr>() + ::core::mem::size_of::<u16>()
+        }
+    } else {
+        quote! { ::core::mem::size_of::<#repr>() }
+    };
+
+    let mut inner_impl = TokenStream2::new();
+    let mut variant_lens = Vec::with_capacity(data.variants.len());
+
+    for encoded in encoded_variants(&data, &global_param)? {
+        let EncodedVariant {
+            variant,
+            pattern,
+            captures,
+            fields,
+            ..
+        } = encoded;
+        let ident = &variant.ident;
+        let field_impl = size_fields_impl(&fields);
+        variant_lens.push(fixed_len_impl(&fields, &import));
+
+        inner_impl.append_all(quote_spanned! { variant.span() =>
+            Self::#ident #pattern => {
+                #captures
+                #field_impl
+            }
+        });
+    }
+
+    // The enum has a fixed size only if all of its variants have fixed size
+    // and all these sizes are equal
+    let fixed_len = match variant_lens.split_first() {
+        Some((first, rest)) => quote! {
+            {
+                let len: Option<usize> = #first;
+                #(
+                    let len = match (len, #rest) {
+                        (Some(a), Some(b)) if a == b => Some(a),
+                        _ => None,
+                    };
+                )*
+                match len {
+                    Some(len) => Some(#header_len + len),
+                    None => None,
+                }
+            }
+        },
+        None => quote! { None },
+    };
+
+    Ok(quote! {
+        #[allow(unused_qualifications)]
+        impl #impl_generics #import::StrictSize for #ident_name #ty_generics #where_clause {
+            const LEN: Option<usize> = #fixed_len;
+
+            fn strict_size(&self) -> usize {
+                use #import::StrictSize;
+                let mut len = #header_len;
+                match self {
+                    #inner_impl
+                }
+                len
+            }
+        }
+    })
+}
+
+fn size_fields_impl(fields: &[EncodedField]) -> TokenStream2 {
+    let mut stream = TokenStream2::new();
+
+    for EncodedField { field, member } in fields {
+        stream.append_all(quote_spanned! { field.span() =>
+            len += data.#member.strict_size();
+        })
+    }
+
+    stream
+}
+
+/// Constructs constant expression computing fixed size of the given fields,
+/// which is known only if each of the fields has a fixed size
+fn fixed_len_impl(fields: &[EncodedField], import: &Path) -> TokenStream2 {
+    let tys = fields.iter().map(|encoded| &encoded.field.ty);
+    quote! {
+        {
+            let len: Option<usize> = Some(0);
+            #(
+                let len = match (len, <#tys as #import::StrictSize>::LEN) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    _ => None,
+                };
+            )*
+            len
+        }
+    }
+}
@@ -13,7 +13,8 @@
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
 use amplify::flags::FlagVec;
-use amplify::num::{u1024, u256, u512};
+use amplify::num::{i1024, i256, i512, u1024, u24, u256, u512};
+use std::convert::TryFrom;
 use std::io;
 
 use crate::{Error, StrictDecode, StrictEncode};
@@ -25,10 +26,38 @@ impl StrictEncode for FlagVec {
     }
 }
 
+/// Since [`FlagVec`] is always encoded in its shrunk form, the decoding fails
+/// with [`Error::DataIntegrityError`] if the encoded data contain trailing
+/// zero bytes (i.e. are non-canonical).
 impl StrictDecode for FlagVec {
     #[inline]
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
-        Ok(Self::from_inner(StrictDecode::strict_decode(d)?))
+        let inner = Vec::<u8>::strict_decode(d)?;
+        if inner.last() == Some(&0) {
+            return Err(Error::DataIntegrityError(s!(
+                "non-canonical FlagVec encoding"
+            )));
+        }
+        Ok(Self::from_inner(inner))
+    }
+}
+
+impl StrictEncode for u24 {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let bytes = self.to_le_bytes();
+        e.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+impl StrictDecode for u24 {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut bytes = [0u8; 4];
+        d.read_exact(&mut bytes[..3])?;
+        let val = u32::from_le_bytes(bytes);
+        u24::try_from(val).map_err(|_| {
+            Error::ValueOutOfRange("u24", 0..0x1000000, val as u128)
+        })
     }
 }
 
@@ -76,11 +105,95 @@ impl StrictDecode for u1024 {
     }
 }
 
+impl StrictEncode for i256 {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let bytes = self.to_le_bytes();
+        e.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+impl StrictDecode for i256 {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut bytes = [0u8; 32];
+        d.read_exact(&mut bytes)?;
+        Ok(i256::from_le_bytes(bytes))
+    }
+}
+
+impl StrictEncode for i512 {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let bytes = self.to_le_bytes();
+        e.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+impl StrictDecode for i512 {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut bytes = [0u8; 64];
+        d.read_exact(&mut bytes)?;
+        Ok(i512::from_le_bytes(bytes))
+    }
+}
+
+impl StrictEncode for i1024 {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let bytes = self.to_le_bytes();
+        e.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+impl StrictDecode for i1024 {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut bytes = [0u8; 128];
+        d.read_exact(&mut bytes)?;
+        Ok(i1024::from_le_bytes(bytes))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::test_helpers::test_encoding_roundtrip;
 
+    #[test]
+    fn test_flag_vec() {
+        test_encoding_roundtrip(&FlagVec::from_inner(vec![0x05]), [1, 0, 0x05])
+            .unwrap();
+        test_encoding_roundtrip(
+            &FlagVec::from_inner(vec![0x00, 0x80]),
+            [2, 0, 0x00, 0x80],
+        )
+        .unwrap();
+        assert_eq!(
+            FlagVec::strict_deserialize(&[2, 0, 0x05, 0x00]),
+            Err(Error::DataIntegrityError(s!(
+                "non-canonical FlagVec encoding"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_u24() {
+        test_encoding_roundtrip(
+            &u24::try_from(0xFFFFFF_u32).unwrap(),
+            [0xFF; 3],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &u24::try_from(0x000001_u32).unwrap(),
+            [1, 0, 0],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &u24::try_from(0xcafe45_u32).unwrap(),
+            [0x45, 0xfe, 0xca],
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_large_uints() {
         test_encoding_roundtrip(
@@ -128,4 +241,21 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_large_ints() {
+        let mut bytes = [0xFFu8; 128];
+        bytes[0] = 0xFE;
+        test_encoding_roundtrip(&i256::from(-2i64), &bytes[..32]).unwrap();
+        test_encoding_roundtrip(&i512::from(-2i64), &bytes[..64]).unwrap();
+        test_encoding_roundtrip(&i1024::from(-2i64), &bytes[..]).unwrap();
+
+        let mut bytes = [0x00u8; 128];
+        bytes[..8]
+            .copy_from_slice(&[0xef, 0xbe, 0xad, 0x4b, 0xfe, 0xca, 0xad, 0x5e]);
+        let val = 0x_5ead_cafe_4bad_beef_i64;
+        test_encoding_roundtrip(&i256::from(val), &bytes[..32]).unwrap();
+        test_encoding_roundtrip(&i512::from(val), &bytes[..64]).unwrap();
+        test_encoding_roundtrip(&i1024::from(val), &bytes[..]).unwrap();
+    }
 }
@@ -15,15 +15,18 @@
 use std::io;
 
 use bitcoin::bech32::u5;
+use bitcoin::blockdata::script::Instruction;
 use bitcoin::util::address::{self, Address};
-use bitcoin::util::psbt::PartiallySignedTransaction;
+use bitcoin::util::merkleblock;
+use bitcoin::util::psbt::{self, PartiallySignedTransaction};
 use bitcoin::{
-    secp256k1, util::bip32, Amount, BlockHash, OutPoint, PubkeyHash, Script,
-    ScriptHash, SigHash, Transaction, TxIn, TxOut, Txid, WPubkeyHash,
-    WScriptHash, Wtxid, XpubIdentifier,
+    secp256k1, util::bip32, Amount, Block, BlockHash, BlockHeader, OutPoint,
+    PubkeyHash, Script, ScriptHash, SigHash, SigHashType, SignedAmount,
+    Transaction, TxIn, TxMerkleNode, TxOut, Txid, WPubkeyHash, WScriptHash,
+    WitnessMerkleNode, Wtxid, XpubIdentifier,
 };
 
-use crate::{strategies, Error, Strategy, StrictDecode, StrictEncode};
+use crate::{strategies, DictVec, Error, Strategy, StrictDecode, StrictEncode};
 
 impl Strategy for Txid {
     type Strategy = strategies::HashFixedBytes;
@@ -52,21 +55,61 @@ impl Strategy for WScriptHash {
 impl Strategy for SigHash {
     type Strategy = strategies::HashFixedBytes;
 }
+impl Strategy for TxMerkleNode {
+    type Strategy = strategies::HashFixedBytes;
+}
+impl Strategy for WitnessMerkleNode {
+    type Strategy = strategies::HashFixedBytes;
+}
 
 impl StrictEncode for secp256k1::SecretKey {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self[..])?)
+        let data = &self[..];
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
+/// Reads secret data into the provided buffer and constructs the value out of
+/// them with `f`. If `zeroize` feature is enabled, the buffer is zeroized
+/// afterwards both on success and failure (including partial reads).
+fn read_secret<D, T>(
+    mut d: D,
+    buf: &mut [u8],
+    f: impl FnOnce(&[u8]) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    D: io::Read,
+{
+    let res = d.read_exact(buf).map_err(Error::from).and_then(|_| f(buf));
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    res
+}
+
+/// Writes secret data from the provided buffer. If `zeroize` feature is
+/// enabled, the buffer is zeroized afterwards both on success and failure.
+fn write_secret<E>(mut e: E, buf: &mut [u8]) -> Result<usize, Error>
+where
+    E: io::Write,
+{
+    let res = e.write_all(buf).map(|_| buf.len()).map_err(Error::from);
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    res
+}
+
 impl StrictDecode for secp256k1::SecretKey {
     #[inline]
-    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
         let mut buf = [0u8; secp256k1::constants::SECRET_KEY_SIZE];
-        d.read_exact(&mut buf)?;
-        Self::from_slice(&buf).map_err(|_| {
-            Error::DataIntegrityError("invalid private key data".to_string())
+        read_secret(d, &mut buf, |data| {
+            Self::from_slice(data).map_err(|_| {
+                Error::DataIntegrityError(
+                    "invalid private key data".to_string(),
+                )
+            })
         })
     }
 }
@@ -74,7 +117,9 @@ impl StrictDecode for secp256k1::SecretKey {
 impl StrictEncode for secp256k1::PublicKey {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.serialize())?)
+        let data = self.serialize();
+        e.write_all(&data)?;
+        Ok(data.len())
     }
 }
 
@@ -98,7 +143,10 @@ impl StrictDecode for secp256k1::PublicKey {
 impl StrictEncode for secp256k1::schnorrsig::PublicKey {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&[0x02_u8])? + e.write(&self.serialize())?)
+        let data = self.serialize();
+        e.write_all(&[0x02_u8])?;
+        e.write_all(&data)?;
+        Ok(1 + data.len())
     }
 }
 
@@ -127,7 +175,9 @@ impl StrictDecode for secp256k1::schnorrsig::PublicKey {
 impl StrictEncode for secp256k1::Signature {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.serialize_compact())?)
+        let data = self.serialize_compact();
+        e.write_all(&data)?;
+        Ok(data.len())
     }
 }
 
@@ -144,10 +194,86 @@ impl StrictDecode for secp256k1::Signature {
     }
 }
 
+/// ECDSA signature wrapper, which is strictly encoded using variable-length
+/// DER format prefixed with a single-byte length (unlike
+/// [`secp256k1::Signature`], which is encoded in 64-byte compact form).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DerSignature(pub secp256k1::Signature);
+
+impl From<secp256k1::Signature> for DerSignature {
+    #[inline]
+    fn from(sig: secp256k1::Signature) -> Self {
+        DerSignature(sig)
+    }
+}
+
+impl From<DerSignature> for secp256k1::Signature {
+    #[inline]
+    fn from(sig: DerSignature) -> Self {
+        sig.0
+    }
+}
+
+impl StrictEncode for DerSignature {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let der = self.0.serialize_der();
+        let len = (der.len() as u8).strict_encode(&mut e)?;
+        e.write_all(&der)?;
+        Ok(len + der.len())
+    }
+}
+
+impl StrictDecode for DerSignature {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = u8::strict_decode(&mut d)?;
+        let mut buf = vec![0u8; len as usize];
+        d.read_exact(&mut buf)?;
+        secp256k1::Signature::from_der(&buf)
+            .map(DerSignature)
+            .map_err(|_| {
+                Error::DataIntegrityError(s!(
+                    "Invalid DER-encoded secp256k1 ECDSA signature data"
+                ))
+            })
+    }
+}
+
+impl StrictEncode for SigHashType {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        // All standard sighash flags fit into a single byte
+        (self.as_u32() as u8).strict_encode(e)
+    }
+}
+
+impl StrictDecode for SigHashType {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(match u8::strict_decode(d)? {
+            0x01 => SigHashType::All,
+            0x02 => SigHashType::None,
+            0x03 => SigHashType::Single,
+            0x81 => SigHashType::AllPlusAnyoneCanPay,
+            0x82 => SigHashType::NonePlusAnyoneCanPay,
+            0x83 => SigHashType::SinglePlusAnyoneCanPay,
+            unknown => {
+                return Err(Error::EnumValueNotKnown(
+                    "SigHashType",
+                    unknown as usize,
+                ))
+            }
+        })
+    }
+}
+
+// TODO: Implement strict encoding for taproot `SchnorrSighashType` once the
+//       bitcoin dependency is upgraded to the version supporting taproot.
+
 impl StrictEncode for secp256k1::schnorrsig::Signature {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self[..])?)
+        let data = &self[..];
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
@@ -174,11 +300,15 @@ impl StrictDecode for secp256k1::schnorrsig::Signature {
 impl StrictEncode for bitcoin::PublicKey {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(if self.compressed {
-            e.write(&self.key.serialize())?
+        if self.compressed {
+            let data = self.key.serialize();
+            e.write_all(&data)?;
+            Ok(data.len())
         } else {
-            e.write(&self.key.serialize_uncompressed())?
-        })
+            let data = self.key.serialize_uncompressed();
+            e.write_all(&data)?;
+            Ok(data.len())
+        }
     }
 }
 
@@ -234,6 +364,263 @@ impl Strategy for PartiallySignedTransaction {
     type Strategy = strategies::BitcoinConsensus;
 }
 
+impl StrictEncode for psbt::Input {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        bitcoin::consensus::serialize(self).strict_encode(e)
+    }
+}
+
+impl StrictDecode for psbt::Input {
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(bitcoin::consensus::deserialize(&Vec::<u8>::strict_decode(
+            d,
+        )?)?)
+    }
+}
+
+impl StrictEncode for psbt::Output {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        bitcoin::consensus::serialize(self).strict_encode(e)
+    }
+}
+
+impl StrictDecode for psbt::Output {
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(bitcoin::consensus::deserialize(&Vec::<u8>::strict_decode(
+            d,
+        )?)?)
+    }
+}
+impl Strategy for bitcoin::VarInt {
+    type Strategy = strategies::BitcoinConsensus;
+}
+impl Strategy for BlockHeader {
+    type Strategy = strategies::BitcoinConsensus;
+}
+impl Strategy for Block {
+    type Strategy = strategies::BitcoinConsensus;
+}
+impl Strategy for merkleblock::MerkleBlock {
+    type Strategy = strategies::BitcoinConsensus;
+}
+impl Strategy for merkleblock::PartialMerkleTree {
+    type Strategy = strategies::BitcoinConsensus;
+}
+
+/// Transaction annotated with mempool-specific information: the fee paid by
+/// the transaction and the number of its unconfirmed ancestors.
+///
+/// Strict encoding of the structure is a consensus-encoded transaction followed
+/// by 8-byte fee amount (in satoshis) and 4-byte ancestor count.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnnotatedTx {
+    tx: Transaction,
+    fee: Amount,
+    ancestor_count: u32,
+}
+
+impl AnnotatedTx {
+    /// Constructs annotated transaction from its components
+    #[inline]
+    pub fn new(tx: Transaction, fee: Amount, ancestor_count: u32) -> Self {
+        AnnotatedTx {
+            tx,
+            fee,
+            ancestor_count,
+        }
+    }
+
+    /// Returns reference to the annotated transaction
+    #[inline]
+    pub fn tx(&self) -> &Transaction {
+        &self.tx
+    }
+
+    /// Returns fee paid by the transaction
+    #[inline]
+    pub fn fee(&self) -> Amount {
+        self.fee
+    }
+
+    /// Returns number of unconfirmed transaction ancestors
+    #[inline]
+    pub fn ancestor_count(&self) -> u32 {
+        self.ancestor_count
+    }
+
+    /// Releases annotation, returning the transaction
+    #[inline]
+    pub fn into_tx(self) -> Transaction {
+        self.tx
+    }
+}
+
+impl StrictEncode for AnnotatedTx {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(strict_encode_list!(e; self.tx, self.fee, self.ancestor_count))
+    }
+}
+
+impl StrictDecode for AnnotatedTx {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; tx, fee, ancestor_count; crate))
+    }
+}
+
+/// PSBT proprietary field (key-value pair), identified by its `prefix` and
+/// `subtype`
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ProprietaryField {
+    /// Proprietary key identifier prefix
+    pub prefix: Vec<u8>,
+    /// Proprietary key subtype
+    pub subtype: u64,
+    /// Proprietary key data
+    pub key: Vec<u8>,
+    /// Field value
+    pub value: Vec<u8>,
+}
+
+impl ProprietaryField {
+    /// Constructs field from PSBT proprietary key and the value
+    pub fn with(key: &psbt::raw::ProprietaryKey, value: &[u8]) -> Self {
+        ProprietaryField {
+            prefix: key.prefix.clone(),
+            subtype: key.subtype as u64,
+            key: key.key.clone(),
+            value: value.to_vec(),
+        }
+    }
+
+    /// Extracts all proprietary fields with the given `prefix` from global,
+    /// input and output maps of the PSBT (in this order)
+    pub fn extract_from_psbt(
+        psbt: &PartiallySignedTransaction,
+        prefix: &[u8],
+    ) -> Vec<ProprietaryField> {
+        psbt.global
+            .proprietary
+            .iter()
+            .chain(psbt.inputs.iter().flat_map(|input| &input.proprietary))
+            .chain(psbt.outputs.iter().flat_map(|output| &output.proprietary))
+            .filter(|(key, _)| key.prefix == prefix)
+            .map(|(key, value)| ProprietaryField::with(key, value))
+            .collect()
+    }
+}
+
+impl StrictEncode for ProprietaryField {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(
+            strict_encode_list!(e; self.prefix, self.subtype, self.key, self.value),
+        )
+    }
+}
+
+impl StrictDecode for ProprietaryField {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(strict_decode_self!(d; prefix, subtype, key, value; crate))
+    }
+}
+
+/// Decodes strict-encoded vector of transactions from untrusted data, failing
+/// with [`Error::ExceedMaxItems`] if the number of transactions exceeds
+/// `max_txs` and with [`Error::DataIntegrityError`] if the decoding requires
+/// more than `max_bytes` of data. Like [`crate::strict_deserialize`], fails
+/// if the data are not consumed entirely.
+pub fn strict_decode_tx_vec_bounded(
+    data: impl AsRef<[u8]>,
+    max_txs: usize,
+    max_bytes: usize,
+) -> Result<Vec<Transaction>, Error> {
+    let mut data = data.as_ref();
+    let mut reader =
+        crate::StrictReader::new(io::Read::take(&mut data, max_bytes as u64));
+
+    let res = (|| {
+        let count = usize::strict_decode(&mut reader)?;
+        if count > max_txs {
+            return Err(Error::ExceedMaxItems(count));
+        }
+        (0..count)
+            .map(|_| Transaction::strict_decode(&mut reader))
+            .collect::<Result<Vec<_>, _>>()
+    })();
+    let consumed = reader.position();
+
+    // Data ended at the limit, while the source has more of them
+    if matches!(res, Err(Error::UnexpectedEof))
+        && consumed == max_bytes
+        && !data.is_empty()
+    {
+        return Err(Error::DataIntegrityError(format!(
+            "transaction data exceed the limit of {} bytes",
+            max_bytes
+        )));
+    }
+    let txs = res?;
+    if !data.is_empty() {
+        return Err(Error::DataNotEntirelyConsumed);
+    }
+    Ok(txs)
+}
+
+/// Decodes strict-encoded witness stack (in bitcoin 0.26 represented by
+/// `Vec<Vec<u8>>`) from untrusted input, failing with
+/// [`Error::ValueOutOfRange`] if it contains more than `max_elements` elements
+/// or any of the elements is larger than `max_element_size` bytes. Limits are
+/// checked before the memory for the elements is allocated.
+// TODO: Convert into `Witness::strict_decode_bounded` once the bitcoin
+//       dependency is upgraded to the version providing `Witness` type.
+pub fn strict_decode_witness_bounded<D: io::Read>(
+    mut d: D,
+    max_elements: usize,
+    max_element_size: usize,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let count = usize::strict_decode(&mut d)?;
+    if count > max_elements {
+        return Err(Error::ValueOutOfRange(
+            "witness elements count",
+            0..max_elements as u128 + 1,
+            count as u128,
+        ));
+    }
+    let mut witness = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = usize::strict_decode(&mut d)?;
+        if len > max_element_size {
+            return Err(Error::ValueOutOfRange(
+                "witness element size",
+                0..max_element_size as u128 + 1,
+                len as u128,
+            ));
+        }
+        let mut element = vec![0u8; len];
+        d.read_exact(&mut element)?;
+        witness.push(element);
+    }
+    Ok(witness)
+}
+
+/// Decodes [`PartiallySignedTransaction`] and verifies that it re-encodes to
+/// exactly the same data which were consumed during the decoding, failing
+/// with [`Error::DataIntegrityError`] otherwise (for instance, if the source
+/// data contain map keys which are not canonically ordered).
+pub fn strict_decode_psbt_canonical<D: io::Read>(
+    d: D,
+) -> Result<PartiallySignedTransaction, Error> {
+    let mut reader = crate::StrictReader::with_recording(d);
+    let psbt = PartiallySignedTransaction::strict_decode(&mut reader)?;
+    if Some(psbt.strict_serialize()?.as_slice()) != reader.recorded() {
+        return Err(Error::DataIntegrityError(s!("non-canonical PSBT")));
+    }
+    Ok(psbt)
+}
+
 impl StrictEncode for address::Payload {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
         Ok(match self {
@@ -301,10 +688,66 @@ impl StrictDecode for Amount {
     }
 }
 
+/// Strict-encodes taproot output commitment data: internal x-only public key
+/// followed by the optional script tree merkle root. The data can be decoded
+/// as a `(schnorrsig::PublicKey, Option<sha256::Hash>)` tuple.
+// TODO: Use `TapBranchHash` for the merkle root (and add `TapTweakHash`
+//       strict encoding) once the bitcoin dependency is upgraded to the
+//       version supporting taproot; bitcoin 0.26 does not define taproot
+//       tagged hash types, so the root is represented by its raw SHA256 value.
+pub fn strict_encode_taproot_output(
+    internal_key: &secp256k1::schnorrsig::PublicKey,
+    merkle_root: Option<bitcoin::hashes::sha256::Hash>,
+) -> Result<Vec<u8>, Error> {
+    let mut e = vec![];
+    strict_encode_list!(e; internal_key, merkle_root);
+    Ok(e)
+}
+
+/// Maximum number of satoshis which may exist according to the bitcoin
+/// consensus rules (21 million bitcoins).
+const MAX_MONEY: u64 = 21_000_000 * 100_000_000;
+
+/// Decodes strict-encoded vector of [`Amount`]s and returns their total,
+/// failing with [`Error::ValueOutOfRange`] if the sum exceeds the total amount
+/// of money which may exist in bitcoin (21 million bitcoins). Like
+/// [`crate::strict_deserialize`], fails if the data are not consumed entirely.
+pub fn strict_decode_amounts_sum(
+    data: impl AsRef<[u8]>,
+) -> Result<Amount, Error> {
+    let amounts: Vec<Amount> = crate::strict_deserialize(data)?;
+    let mut sum = 0u64;
+    for amount in amounts {
+        sum = sum
+            .checked_add(amount.as_sat())
+            .filter(|sum| *sum <= MAX_MONEY)
+            .ok_or_else(|| {
+                Error::ValueOutOfRange(
+                    "amounts sum",
+                    0..MAX_MONEY as u128 + 1,
+                    sum as u128 + amount.as_sat() as u128,
+                )
+            })?;
+    }
+    Ok(Amount::from_sat(sum))
+}
+
+impl StrictEncode for SignedAmount {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        self.as_sat().strict_encode(e)
+    }
+}
+
+impl StrictDecode for SignedAmount {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(SignedAmount::from_sat(i64::strict_decode(d)?))
+    }
+}
+
 impl StrictEncode for Script {
     #[inline]
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
-        self.to_bytes().strict_encode(e)
+        self.as_bytes().strict_encode(e)
     }
 }
 
@@ -315,6 +758,338 @@ impl StrictDecode for Script {
     }
 }
 
+/// Maximum size of a single data push in a script according to bitcoin
+/// consensus rules.
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+/// Maximum size of a script according to bitcoin consensus rules.
+pub const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// Decodes strict-encoded [`Script`] enforcing bitcoin consensus limits: the
+/// script must not be larger than [`MAX_SCRIPT_SIZE`] bytes, must consist of
+/// well-formed instructions and each of its data pushes must not exceed
+/// [`MAX_SCRIPT_ELEMENT_SIZE`] bytes. Violations are reported as
+/// [`Error::DataIntegrityError`].
+pub fn strict_decode_script_consensus_limited<D: io::Read>(
+    mut d: D,
+) -> Result<Script, Error> {
+    let len = usize::strict_decode(&mut d)?;
+    if len > MAX_SCRIPT_SIZE {
+        return Err(Error::DataIntegrityError(format!(
+            "script size {} exceeds consensus limit of {} bytes",
+            len, MAX_SCRIPT_SIZE
+        )));
+    }
+    let mut data = vec![0u8; len];
+    d.read_exact(&mut data)?;
+    let script = Script::from(data);
+    for instruction in script.instructions() {
+        let instruction = instruction.map_err(|err| {
+            Error::DataIntegrityError(format!("invalid script: {}", err))
+        })?;
+        if let Instruction::PushBytes(push) = instruction {
+            if push.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                return Err(Error::DataIntegrityError(format!(
+                    "script push of {} bytes exceeds consensus limit of {} \
+                    bytes",
+                    push.len(),
+                    MAX_SCRIPT_ELEMENT_SIZE
+                )));
+            }
+        }
+    }
+    Ok(script)
+}
+
+/// Script wrapper which is strictly encoded with compression of the standard
+/// script patterns: a single tag byte followed by the hash for P2PKH (`0`),
+/// P2SH (`1`), P2WPKH (`2`) and P2WSH (`3`) scripts, or tag `0xFF` followed by
+/// the strict encoding of the script for all other scripts.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct CompressedScript(Script);
+
+impl StrictEncode for CompressedScript {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let bytes = self.0.as_bytes();
+        let (tag, hash) = if self.0.is_p2pkh() {
+            (0u8, &bytes[3..23])
+        } else if self.0.is_p2sh() {
+            (1u8, &bytes[2..22])
+        } else if self.0.is_v0_p2wpkh() {
+            (2u8, &bytes[2..22])
+        } else if self.0.is_v0_p2wsh() {
+            (3u8, &bytes[2..34])
+        } else {
+            return Ok(strict_encode_list!(e; 0xFFu8, self.0));
+        };
+        let len = tag.strict_encode(&mut e)?;
+        e.write_all(hash)?;
+        Ok(len + hash.len())
+    }
+}
+
+impl StrictDecode for CompressedScript {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let tag = u8::strict_decode(&mut d)?;
+        let (prefix, hash_len, suffix): (&[u8], usize, &[u8]) = match tag {
+            0 => (&[0x76, 0xa9, 0x14], 20, &[0x88, 0xac]),
+            1 => (&[0xa9, 0x14], 20, &[0x87]),
+            2 => (&[0x00, 0x14], 20, &[]),
+            3 => (&[0x00, 0x20], 32, &[]),
+            0xFF => {
+                let script = Script::strict_decode(&mut d)?;
+                if script.is_p2pkh()
+                    || script.is_p2sh()
+                    || script.is_v0_p2wpkh()
+                    || script.is_v0_p2wsh()
+                {
+                    return Err(Error::DataIntegrityError(s!(
+                        "standard script pattern is not compressed"
+                    )));
+                }
+                return Ok(CompressedScript(script));
+            }
+            tag => {
+                return Err(Error::EnumValueNotKnown(
+                    "CompressedScript",
+                    tag as usize,
+                ))
+            }
+        };
+        let mut hash = vec![0u8; hash_len];
+        d.read_exact(&mut hash)?;
+        let mut bytes = prefix.to_vec();
+        bytes.extend(hash);
+        bytes.extend(suffix);
+        Ok(CompressedScript(Script::from(bytes)))
+    }
+}
+
+/// Vector of scripts which is strictly encoded using dictionary encoding
+/// (see [`DictVec`]) with compression of the standard script patterns (P2PKH,
+/// P2SH, P2WPKH and P2WSH) applied to the dictionary entries. Allows compact
+/// representation of sets of transaction outputs with repeated scripts.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ScriptDictVec(Vec<Script>);
+
+impl ScriptDictVec {
+    /// Constructs empty vector
+    #[inline]
+    pub fn new() -> Self {
+        ScriptDictVec(Vec::new())
+    }
+
+    /// Returns reference to the underlying `Vec`
+    #[inline]
+    pub fn as_inner(&self) -> &Vec<Script> {
+        &self.0
+    }
+
+    /// Returns mutable reference to the underlying `Vec`
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut Vec<Script> {
+        &mut self.0
+    }
+
+    /// Converts into the underlying `Vec`
+    #[inline]
+    pub fn into_inner(self) -> Vec<Script> {
+        self.0
+    }
+}
+
+impl From<Vec<Script>> for ScriptDictVec {
+    #[inline]
+    fn from(vec: Vec<Script>) -> Self {
+        ScriptDictVec(vec)
+    }
+}
+
+impl From<ScriptDictVec> for Vec<Script> {
+    #[inline]
+    fn from(vec: ScriptDictVec) -> Self {
+        vec.0
+    }
+}
+
+impl StrictEncode for ScriptDictVec {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        DictVec::from(
+            self.0
+                .iter()
+                .cloned()
+                .map(CompressedScript)
+                .collect::<Vec<_>>(),
+        )
+        .strict_encode(e)
+    }
+}
+
+impl StrictDecode for ScriptDictVec {
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(ScriptDictVec(
+            DictVec::<CompressedScript>::strict_decode(d)?
+                .into_inner()
+                .into_iter()
+                .map(|script| script.0)
+                .collect(),
+        ))
+    }
+}
+
+/// Transaction outputs sorted by their value, which are strictly encoded
+/// together with an index allowing binary search of an output by its value
+/// directly in the encoded data (see [`ValueSortedTxOuts::find_by_value`]).
+///
+/// Strict encoding starts with `u16` number of outputs, followed by the index
+/// entries consisting of `u64` output value and `u32` offset of the output
+/// encoding (counting from the end of the index), followed by the strict
+/// encodings of the outputs, sorted by their value.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ValueSortedTxOuts(Vec<TxOut>);
+
+/// Length of the [`ValueSortedTxOuts`] index entry
+const TXOUT_INDEX_ENTRY_LEN: usize = 12;
+
+impl ValueSortedTxOuts {
+    /// Constructs sorted outputs from a list of outputs. Outputs with the
+    /// same value retain their original order.
+    pub fn new(mut txouts: Vec<TxOut>) -> Self {
+        txouts.sort_by_key(|txout| txout.value);
+        ValueSortedTxOuts(txouts)
+    }
+
+    /// Returns reference to the underlying `Vec` of outputs sorted by value
+    #[inline]
+    pub fn as_inner(&self) -> &Vec<TxOut> {
+        &self.0
+    }
+
+    /// Converts into the underlying `Vec` of outputs sorted by value
+    #[inline]
+    pub fn into_inner(self) -> Vec<TxOut> {
+        self.0
+    }
+
+    /// Finds output with the given value in the strict-encoded
+    /// [`ValueSortedTxOuts`] data using binary search over the index, decoding
+    /// only the found output. If there are multiple outputs with the same
+    /// value, returns the first one of them.
+    pub fn find_by_value(
+        data: impl AsRef<[u8]>,
+        sats: u64,
+    ) -> Result<Option<TxOut>, Error> {
+        let mut data = data.as_ref();
+        let count = usize::strict_decode(&mut data)?;
+        let index_len = count * TXOUT_INDEX_ENTRY_LEN;
+        if data.len() < index_len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let (index, txouts) = data.split_at(index_len);
+        let entry = |no: usize| -> Result<(u64, u32), Error> {
+            let mut entry = &index[no * TXOUT_INDEX_ENTRY_LEN..];
+            Ok((
+                u64::strict_decode(&mut entry)?,
+                u32::strict_decode(&mut entry)?,
+            ))
+        };
+
+        let (mut lo, mut hi) = (0usize, count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if entry(mid)?.0 < sats {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == count {
+            return Ok(None);
+        }
+        let (value, offset) = entry(lo)?;
+        if value != sats {
+            return Ok(None);
+        }
+        let txout = txouts
+            .get(offset as usize..)
+            .ok_or(io::ErrorKind::UnexpectedEof)
+            .map_err(Error::from)
+            .and_then(TxOut::strict_decode)?;
+        if txout.value != value {
+            return Err(Error::DataIntegrityError(s!(
+                "value-sorted outputs index does not match output value"
+            )));
+        }
+        Ok(Some(txout))
+    }
+}
+
+impl From<Vec<TxOut>> for ValueSortedTxOuts {
+    #[inline]
+    fn from(txouts: Vec<TxOut>) -> Self {
+        ValueSortedTxOuts::new(txouts)
+    }
+}
+
+impl From<ValueSortedTxOuts> for Vec<TxOut> {
+    #[inline]
+    fn from(txouts: ValueSortedTxOuts) -> Self {
+        txouts.0
+    }
+}
+
+impl StrictEncode for ValueSortedTxOuts {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let mut payload = vec![];
+        let mut len = self.0.len().strict_encode(&mut e)?;
+        for txout in &self.0 {
+            let offset = payload.len() as u32;
+            len += strict_encode_list!(e; txout.value, offset);
+            txout.strict_encode(&mut payload)?;
+        }
+        e.write_all(&payload)?;
+        Ok(len + payload.len())
+    }
+}
+
+/// Strict decoding of [`ValueSortedTxOuts`] fails with
+/// [`Error::DataIntegrityError`] if the outputs are not sorted by value or the
+/// index does not match the actual outputs values and offsets.
+impl StrictDecode for ValueSortedTxOuts {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let count = usize::strict_decode(&mut d)?;
+        let index = (0..count)
+            .map(|_| {
+                Ok((u64::strict_decode(&mut d)?, u32::strict_decode(&mut d)?))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut txouts = Vec::with_capacity(count);
+        let mut offset = 0usize;
+        let mut prev = 0u64;
+        for (value, txout_offset) in index {
+            let (txout, len): (TxOut, _) =
+                strategies::BitcoinConsensus::decode_consuming(&mut d)?;
+            if txout.value != value || txout_offset as usize != offset {
+                return Err(Error::DataIntegrityError(s!(
+                    "value-sorted outputs index does not match outputs"
+                )));
+            }
+            if value < prev {
+                return Err(Error::DataIntegrityError(s!(
+                    "outputs are not sorted by value"
+                )));
+            }
+            txouts.push(txout);
+            offset += len;
+            prev = value;
+        }
+        Ok(ValueSortedTxOuts(txouts))
+    }
+}
+
 impl StrictEncode for bitcoin::Network {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -334,6 +1109,35 @@ impl StrictDecode for bitcoin::Network {
     }
 }
 
+impl StrictEncode for bitcoin::PrivateKey {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(strict_encode_list!(e; self.network, self.compressed, self.key))
+    }
+}
+
+impl StrictDecode for bitcoin::PrivateKey {
+    #[inline]
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        Ok(bitcoin::PrivateKey {
+            network: bitcoin::Network::strict_decode(&mut d)?,
+            compressed: bool::strict_decode(&mut d)?,
+            key: secp256k1::SecretKey::strict_decode(&mut d)?,
+        })
+    }
+}
+
+/// Decodes strict-encoded [`bitcoin::Network`], returning its raw magic
+/// number as an inner `Err` if the magic does not correspond to any of the
+/// networks known to the bitcoin library (for instance, for custom signet
+/// networks). The magic can be encoded back with its `u32` strict encoding.
+pub fn strict_decode_network_or_magic<D: io::Read>(
+    d: D,
+) -> Result<Result<bitcoin::Network, u32>, Error> {
+    let magic = u32::strict_decode(d)?;
+    Ok(bitcoin::Network::from_magic(magic).ok_or(magic))
+}
+
 impl StrictEncode for bip32::ChildNumber {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -362,9 +1166,9 @@ impl StrictDecode for bip32::ChildNumber {
 impl StrictEncode for bip32::DerivationPath {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        let buf: Vec<bip32::ChildNumber> =
-            self.into_iter().map(bip32::ChildNumber::clone).collect();
-        buf.strict_encode(&mut e)
+        let len = self.as_ref().len().strict_encode(&mut e)?;
+        self.into_iter()
+            .try_fold(len, |len, child| Ok(len + child.strict_encode(&mut e)?))
     }
 }
 
@@ -377,10 +1181,90 @@ impl StrictDecode for bip32::DerivationPath {
     }
 }
 
+/// BIP32 child number wrapper, which is strictly encoded as a standard 4-byte
+/// BIP32 index with the high bit set for hardened derivation (unlike
+/// [`bip32::ChildNumber`], which is encoded as a tag byte followed by 4-byte
+/// index).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CompactChildNumber(pub bip32::ChildNumber);
+
+impl From<bip32::ChildNumber> for CompactChildNumber {
+    #[inline]
+    fn from(child: bip32::ChildNumber) -> Self {
+        CompactChildNumber(child)
+    }
+}
+
+impl From<CompactChildNumber> for bip32::ChildNumber {
+    #[inline]
+    fn from(child: CompactChildNumber) -> Self {
+        child.0
+    }
+}
+
+impl StrictEncode for CompactChildNumber {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        u32::from(self.0).strict_encode(e)
+    }
+}
+
+impl StrictDecode for CompactChildNumber {
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        Ok(CompactChildNumber(bip32::ChildNumber::from(
+            u32::strict_decode(d)?,
+        )))
+    }
+}
+
+/// BIP32 derivation path wrapper, which is strictly encoded as a list of
+/// [`CompactChildNumber`]s, i.e. using 4 bytes per each path component
+/// (unlike [`bip32::DerivationPath`], which uses 5 bytes per component).
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CompactDerivationPath(pub bip32::DerivationPath);
+
+impl From<bip32::DerivationPath> for CompactDerivationPath {
+    #[inline]
+    fn from(path: bip32::DerivationPath) -> Self {
+        CompactDerivationPath(path)
+    }
+}
+
+impl From<CompactDerivationPath> for bip32::DerivationPath {
+    #[inline]
+    fn from(path: CompactDerivationPath) -> Self {
+        path.0
+    }
+}
+
+impl StrictEncode for CompactDerivationPath {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let path = self.0.as_ref();
+        let mut len = path.len().strict_encode(&mut e)?;
+        for child in path {
+            len += CompactChildNumber(*child).strict_encode(&mut e)?;
+        }
+        Ok(len)
+    }
+}
+
+impl StrictDecode for CompactDerivationPath {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut d)?;
+        let path = (0..len)
+            .map(|_| CompactChildNumber::strict_decode(&mut d).map(|c| c.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(CompactDerivationPath(path.into()))
+    }
+}
+
 impl StrictEncode for bip32::ChainCode {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(self.as_bytes())?)
+        let data = self.as_bytes();
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
@@ -396,7 +1280,9 @@ impl StrictDecode for bip32::ChainCode {
 impl StrictEncode for bip32::Fingerprint {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(self.as_bytes())?)
+        let data = self.as_bytes();
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
@@ -411,7 +1297,9 @@ impl StrictDecode for bip32::Fingerprint {
 
 impl StrictEncode for bip32::ExtendedPubKey {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.encode())?)
+        let data = self.encode();
+        e.write_all(&data)?;
+        Ok(data.len())
     }
 }
 
@@ -430,20 +1318,21 @@ impl StrictDecode for bip32::ExtendedPubKey {
 
 impl StrictEncode for bip32::ExtendedPrivKey {
     #[inline]
-    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.encode())?)
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        write_secret(e, &mut self.encode())
     }
 }
 
 impl StrictDecode for bip32::ExtendedPrivKey {
     #[inline]
-    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
         let mut buf = [0u8; 78];
-        d.read_exact(&mut buf)?;
-        bip32::ExtendedPrivKey::decode(&buf).map_err(|_| {
-            Error::DataIntegrityError(
-                "Extended privkey integrity is broken".to_string(),
-            )
+        read_secret(d, &mut buf, |data| {
+            bip32::ExtendedPrivKey::decode(data).map_err(|_| {
+                Error::DataIntegrityError(
+                    "Extended privkey integrity is broken".to_string(),
+                )
+            })
         })
     }
 }
@@ -455,7 +1344,9 @@ pub(crate) mod test {
     use bitcoin::{
         consensus, hashes::hex::FromHex, hashes::Hash, secp256k1::Message,
     };
-    use bitcoin_hashes::{hash160, hmac, ripemd160, sha256, sha256d, sha256t};
+    use bitcoin_hashes::{
+        hash160, hmac, ripemd160, sha256, sha256d, sha256t, sha512,
+    };
 
     use super::*;
     use crate::test_helpers::*;
@@ -467,6 +1358,14 @@ pub(crate) mod test {
             0xeb, 0x11, 0x98, 0x70, 0x84, 0x4e, 0xdb, 0x25, 0xa0, 0xea, 0x1e,
             0x35, 0x20, 0x7f, 0xaa, 0x44, 0xa9, 0x67, 0xa6, 0xa6, 0x61,
         ];
+        static HASH512_BYTES: [u8; 64] = [
+            0x15, 0x2d, 0x1c, 0x97, 0x61, 0xd4, 0x64, 0x66, 0x68, 0xdf, 0xcd,
+            0xeb, 0x11, 0x98, 0x70, 0x84, 0x4e, 0xdb, 0x25, 0xa0, 0xea, 0x1e,
+            0x35, 0x20, 0x7f, 0xaa, 0x44, 0xa9, 0x67, 0xa6, 0xa6, 0x61, 0x4c,
+            0x2d, 0x0b, 0x3a, 0x5e, 0x96, 0x17, 0xf1, 0x8a, 0x2c, 0x3b, 0x04,
+            0x9d, 0xe0, 0x61, 0x7a, 0x55, 0xc8, 0x02, 0xb7, 0x13, 0x6e, 0xf4,
+            0x39, 0xa1, 0xd5, 0x88, 0x20, 0xcb, 0x7f, 0x46, 0x01,
+        ];
         static HASH160_BYTES: [u8; 20] = [
             0x15, 0x2d, 0x1c, 0x97, 0x61, 0xd4, 0x64, 0x66, 0x68, 0xdf, 0xcd,
             0xeb, 0x11, 0x98, 0x4e, 0xdb, 0x25, 0xa0, 0xea, 0x1e,
@@ -506,6 +1405,16 @@ pub(crate) mod test {
             HASH256_BYTES,
         )
         .unwrap();
+        test_encoding_roundtrip(
+            &sha512::Hash::from_inner(HASH512_BYTES),
+            HASH512_BYTES,
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &hmac::Hmac::<sha512::Hash>::from_inner(HASH512_BYTES),
+            HASH512_BYTES,
+        )
+        .unwrap();
         test_encoding_roundtrip(
             &sha256::Hash::from_inner(HASH256_BYTES),
             HASH256_BYTES,
@@ -566,6 +1475,16 @@ pub(crate) mod test {
             HASH256_BYTES,
         )
         .unwrap();
+        test_encoding_roundtrip(
+            &TxMerkleNode::from_slice(&HASH256_BYTES).unwrap(),
+            HASH256_BYTES,
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &WitnessMerkleNode::from_slice(&HASH256_BYTES).unwrap(),
+            HASH256_BYTES,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -585,6 +1504,57 @@ pub(crate) mod test {
         test_encoding_roundtrip(&sk, &SK_BYTES[..])
     }
 
+    #[test]
+    fn test_encoding_private_key() {
+        let wif = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+        let pk = bitcoin::PrivateKey::from_wif(wif).unwrap();
+        assert!(pk.compressed);
+
+        let mut data = bitcoin::Network::Bitcoin.magic().to_le_bytes().to_vec();
+        data.push(0x01);
+        data.extend(&pk.key[..]);
+        test_encoding_roundtrip(&pk, &data).unwrap();
+        assert_eq!(
+            bitcoin::PrivateKey::strict_deserialize(&data)
+                .unwrap()
+                .to_wif(),
+            wif
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn test_secret_zeroize() {
+        let data = [0x15u8; 32];
+
+        let mut buf = [0u8; 32];
+        let sk = read_secret(&data[..], &mut buf, |secret| {
+            assert_eq!(secret, &data[..]);
+            Ok(secp256k1::SecretKey::from_slice(secret).unwrap())
+        })
+        .unwrap();
+        assert_eq!(sk[..], data[..]);
+        assert_eq!(buf, [0u8; 32]);
+
+        let mut buf = [0u8; 32];
+        assert!(read_secret(&data[..], &mut buf, |_| {
+            Err::<(), _>(Error::DataIntegrityError(s!("invalid")))
+        })
+        .is_err());
+        assert_eq!(buf, [0u8; 32]);
+
+        // Partial read
+        let mut buf = [0u8; 32];
+        assert!(read_secret(&data[..16], &mut buf, |_| Ok(())).is_err());
+        assert_eq!(buf, [0u8; 32]);
+
+        let mut buf = data;
+        let mut encoded = vec![];
+        assert_eq!(write_secret(&mut encoded, &mut buf).unwrap(), 32);
+        assert_eq!(encoded, data);
+        assert_eq!(buf, [0u8; 32]);
+    }
+
     #[test]
     fn test_encoding_pubkey() {
         static PK_BYTES_02: [u8; 33] = [
@@ -679,7 +1649,6 @@ pub(crate) mod test {
     }
 
     #[test]
-    #[should_panic(expected = "UnexpectedEof")]
     fn test_garbagedata_pubkey() {
         static PK_BYTES_04: [u8; 60] = [
             0x04, 0x9b, 0x63, 0x47, 0x39, 0x85, 0x05, 0xf5, 0xec, 0x93, 0x82,
@@ -689,7 +1658,10 @@ pub(crate) mod test {
             0x19, 0xeb, 0xfa, 0x57, 0xda, 0x7c, 0xff, 0x3a, 0xff, 0x6e, 0x81,
             0x9e, 0x4e, 0xe9, 0x71, 0xd8,
         ];
-        bitcoin::PublicKey::strict_decode(&PK_BYTES_04[..]).unwrap();
+        assert_eq!(
+            bitcoin::PublicKey::strict_decode(&PK_BYTES_04[..]).unwrap_err(),
+            Error::UnexpectedEof
+        );
     }
 
     #[test]
@@ -767,16 +1739,47 @@ pub(crate) mod test {
     }
 
     #[test]
-    #[should_panic(expected = "UnexpectedEof")]
     fn test_garbagedata_ecdsa() {
-        secp256k1::Signature::strict_decode(&ECDSA_BYTES[5..]).unwrap();
+        assert_eq!(
+            secp256k1::Signature::strict_decode(&ECDSA_BYTES[5..]).unwrap_err(),
+            Error::UnexpectedEof
+        );
     }
 
     #[test]
-    #[should_panic(expected = "UnexpectedEof")]
     fn test_garbagedata_schnorrsig() {
-        secp256k1::schnorrsig::Signature::strict_decode(&SCHNORR_BYTES[5..])
-            .unwrap();
+        assert_eq!(
+            secp256k1::schnorrsig::Signature::strict_decode(
+                &SCHNORR_BYTES[5..]
+            )
+            .unwrap_err(),
+            Error::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_encode_der_signature() {
+        let ecdsa = secp256k1::Signature::from_compact(&ECDSA_BYTES).unwrap();
+        let der = DerSignature::from(ecdsa);
+        let der_bytes = ecdsa.serialize_der();
+
+        let ser = der.strict_serialize().unwrap();
+        assert_eq!(ser[0] as usize, der_bytes.len());
+        assert_eq!(&ser[1..], &der_bytes[..]);
+        test_encoding_roundtrip(&der, &ser).unwrap();
+
+        let decoded = DerSignature::strict_deserialize(&ser).unwrap();
+        assert_eq!(decoded.0, ecdsa);
+        assert_eq!(decoded.0.serialize_compact()[..], ECDSA_BYTES[..]);
+
+        let mut wrong = ser.clone();
+        wrong[1] = 0x00;
+        assert_eq!(
+            DerSignature::strict_deserialize(&wrong),
+            Err(Error::DataIntegrityError(s!(
+                "Invalid DER-encoded secp256k1 ECDSA signature data"
+            )))
+        );
     }
 
     #[test]
@@ -810,6 +1813,97 @@ pub(crate) mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_encoding_sighash_type() {
+        test_encoding_roundtrip(&SigHashType::All, [0x01]).unwrap();
+        test_encoding_roundtrip(&SigHashType::NonePlusAnyoneCanPay, [0x82])
+            .unwrap();
+        test_encoding_roundtrip(&SigHashType::SinglePlusAnyoneCanPay, [0x83])
+            .unwrap();
+        assert_eq!(
+            SigHashType::strict_deserialize([0x84]),
+            Err(Error::EnumValueNotKnown("SigHashType", 0x84))
+        );
+        assert_eq!(
+            SigHashType::strict_deserialize([0x00]),
+            Err(Error::EnumValueNotKnown("SigHashType", 0x00))
+        );
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        let txids: Vec<Txid> = (1u8..=5).map(|no| Txid::hash(&[no])).collect();
+        let matches = [false, true, false, false, true];
+        let tree = merkleblock::PartialMerkleTree::from_txids(&txids, &matches);
+        test_encoding_roundtrip(&tree, consensus::serialize(&tree)).unwrap();
+
+        let decoded = merkleblock::PartialMerkleTree::strict_deserialize(
+            tree.strict_serialize().unwrap(),
+        )
+        .unwrap();
+        let mut matched = vec![];
+        let mut indexes = vec![];
+        let root = decoded.extract_matches(&mut matched, &mut indexes).unwrap();
+        assert_eq!(matched, vec![txids[1], txids[4]]);
+        assert_eq!(indexes, vec![1, 4]);
+
+        let block = merkleblock::MerkleBlock {
+            header: BlockHeader {
+                version: 1,
+                prev_blockhash: BlockHash::hash(b"prev"),
+                merkle_root: root,
+                time: 1231006505,
+                bits: 0x1d00ffff,
+                nonce: 2083236893,
+            },
+            txn: tree,
+        };
+        test_encoding_roundtrip(&block, consensus::serialize(&block)).unwrap();
+    }
+
+    #[test]
+    fn test_encoding_varint() {
+        test_encoding_roundtrip(&bitcoin::VarInt(0), [0x00]).unwrap();
+        test_encoding_roundtrip(&bitcoin::VarInt(0xFC), [0xFC]).unwrap();
+        test_encoding_roundtrip(&bitcoin::VarInt(0xFD), [0xFD, 0xFD, 0x00])
+            .unwrap();
+        test_encoding_roundtrip(&bitcoin::VarInt(0xFFFF), [0xFD, 0xFF, 0xFF])
+            .unwrap();
+        test_encoding_roundtrip(
+            &bitcoin::VarInt(0x10000),
+            [0xFE, 0x00, 0x00, 0x01, 0x00],
+        )
+        .unwrap();
+
+        // Non-minimal encodings
+        assert!(
+            bitcoin::VarInt::strict_deserialize(&[0xFD, 0xFC, 0x00]).is_err()
+        );
+        assert!(bitcoin::VarInt::strict_deserialize(&[
+            0xFE, 0xFF, 0xFF, 0x00, 0x00
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_network_or_magic() {
+        let data = bitcoin::Network::Signet.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_network_or_magic(&data[..]).unwrap(),
+            Ok(bitcoin::Network::Signet)
+        );
+
+        let data = [0xA1u8, 0xA2u8, 0xA3u8, 0xA4u8];
+        assert!(bitcoin::Network::strict_deserialize(&data).is_err());
+        let magic = strict_decode_network_or_magic(&data[..])
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(magic, 0xA4A3A2A1);
+        assert_eq!(magic.strict_serialize().unwrap(), data);
+
+        assert!(strict_decode_network_or_magic(&data[..3]).is_err());
+    }
+
     #[test]
     fn test_encoding_address() {
         test_encoding_roundtrip(
@@ -907,6 +2001,38 @@ pub(crate) mod test {
         .unwrap();
     }
 
+    #[test]
+    fn test_encoding_address_v1() {
+        // Taproot address strings can't be parsed with bitcoin 0.26 (it has no
+        // bech32m support), so we construct the payload directly from the
+        // BIP-350 test vector
+        // tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c
+        let program = Vec::from_hex(
+            "000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+        )
+        .unwrap();
+        let address = Address {
+            network: bitcoin::Network::Testnet,
+            payload: address::Payload::WitnessProgram {
+                version: u5::try_from_u8(1).unwrap(),
+                program: program.clone(),
+            },
+        };
+        assert_eq!(
+            address.script_pubkey(),
+            Script::from(
+                Vec::from_hex(
+                    "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433"
+                )
+                .unwrap()
+            )
+        );
+
+        let mut bytes = vec![0x0B, 0x11, 0x09, 0x07, 0x01, 0x20, 0x00];
+        bytes.extend(&program);
+        test_encoding_roundtrip(&address, bytes).unwrap();
+    }
+
     #[test]
     #[should_panic(
         expected = r#"ValueOutOfRange("witness program version", 0..17, 35)"#
@@ -950,14 +2076,16 @@ pub(crate) mod test {
     }
 
     #[test]
-    #[should_panic(expected = "UnexpectedEof")]
     fn test_garbagedata_outpoint() {
         static OUTPOINT: [u8; 32] = [
             0x53, 0xc6, 0x31, 0x13, 0xed, 0x18, 0x68, 0xfc, 0xa, 0xdf, 0x8e,
             0xcd, 0xfd, 0x1f, 0x4d, 0xd6, 0xe5, 0xe3, 0x85, 0x83, 0xa4, 0x9d,
             0xb, 0x14, 0xe7, 0xf8, 0x87, 0xa4, 0xd1, 0x61, 0x78, 0x21,
         ];
-        OutPoint::strict_decode(&OUTPOINT[..]).unwrap();
+        assert_eq!(
+            OutPoint::strict_decode(&OUTPOINT[..]).unwrap_err(),
+            Error::UnexpectedEof
+        );
     }
 
     #[test]
@@ -969,16 +2097,137 @@ pub(crate) mod test {
         test_encoding_roundtrip(&amount, data).unwrap();
     }
 
+    #[test]
+    fn test_taproot_output() {
+        type TaprootOutput =
+            (secp256k1::schnorrsig::PublicKey, Option<sha256::Hash>);
+
+        let internal_key = secp256k1::schnorrsig::PublicKey::from_str(
+            "18845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166",
+        )
+        .unwrap();
+        let merkle_root = sha256::Hash::hash(b"script tree");
+
+        let data =
+            strict_encode_taproot_output(&internal_key, Some(merkle_root))
+                .unwrap();
+        let mut expected = vec![0x02];
+        expected.extend(&internal_key.serialize()[..]);
+        expected.push(0x01);
+        expected.extend(&merkle_root[..]);
+        assert_eq!(data, expected);
+        assert_eq!(
+            TaprootOutput::strict_deserialize(&data).unwrap(),
+            (internal_key, Some(merkle_root))
+        );
+
+        let data = strict_encode_taproot_output(&internal_key, None).unwrap();
+        assert_eq!(data.len(), 34);
+        assert_eq!(data[33], 0x00);
+        assert_eq!(
+            TaprootOutput::strict_deserialize(&data).unwrap(),
+            (internal_key, None)
+        );
+    }
+
+    #[test]
+    fn test_encoding_derivation_path() {
+        let path =
+            bip32::DerivationPath::from_str("m/84'/0'/0'/1/42/2147483647")
+                .unwrap();
+        let children: Vec<bip32::ChildNumber> =
+            path.into_iter().cloned().collect();
+        let data = children.strict_serialize().unwrap();
+        assert_eq!(data.len(), 2 + 5 * 6);
+        test_encoding_roundtrip(&path, data).unwrap();
+    }
+
+    #[test]
+    fn test_compact_derivation_path() {
+        let path = bip32::DerivationPath::from_str("m/0/1'").unwrap();
+        let compact = CompactDerivationPath::from(path.clone());
+        test_encoding_roundtrip(
+            &compact,
+            [0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x80],
+        )
+        .unwrap();
+        assert_eq!(compact.strict_serialize().unwrap().len(), 2 + 4 * 2);
+        assert_eq!(path.strict_serialize().unwrap().len(), 2 + 5 * 2);
+
+        test_encoding_roundtrip(
+            &CompactChildNumber(bip32::ChildNumber::Hardened { index: 1 }),
+            [0x01, 0x00, 0x00, 0x80],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &CompactChildNumber(bip32::ChildNumber::Normal { index: 1 }),
+            [0x01, 0x00, 0x00, 0x00],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_amounts_sum() {
+        let amounts = vec![
+            Amount::from_sat(MAX_MONEY - 1_000),
+            Amount::from_sat(999),
+            Amount::from_sat(0),
+        ];
+        let data = amounts.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_amounts_sum(&data).unwrap(),
+            Amount::from_sat(MAX_MONEY - 1)
+        );
+
+        let amounts =
+            vec![Amount::from_sat(MAX_MONEY - 1_000), Amount::from_sat(1_001)];
+        let data = amounts.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_amounts_sum(&data).unwrap_err(),
+            Error::ValueOutOfRange(
+                "amounts sum",
+                0..MAX_MONEY as u128 + 1,
+                MAX_MONEY as u128 + 1
+            )
+        );
+
+        // Must not overflow `u64`
+        let amounts = vec![Amount::from_sat(u64::MAX), Amount::from_sat(1)];
+        let data = amounts.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_amounts_sum(&data).unwrap_err(),
+            Error::ValueOutOfRange(
+                "amounts sum",
+                0..MAX_MONEY as u128 + 1,
+                u64::MAX as u128
+            )
+        );
+    }
+
+    #[test]
+    fn test_signed_amount() {
+        let value = -21_000_000_i64;
+        let amount = SignedAmount::from_sat(value);
+        let data = value.to_le_bytes();
+        assert_eq!(data, [0xC0, 0x90, 0xBF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF]);
+        test_encoding_roundtrip(&amount, data).unwrap();
+
+        let amount = SignedAmount::from_sat(21_000_000);
+        test_encoding_roundtrip(&amount, 21_000_000_i64.to_le_bytes()).unwrap();
+    }
+
+    /// Serialized segwit transaction shared by the transaction test cases
+    const TX_SEGWIT_HEX: &str =
+        "02000000000101595895ea20179de87052b4046dfe6fd515860505d6511a9004cf\
+        12a1f93cac7c0100000000ffffffff01deb807000000000017a9140f3444e271620\
+        c736808aa7b33e370bd87cb5a078702483045022100fb60dad8df4af2841adc0346\
+        638c16d0b8035f5e3f3753b88db122e70c79f9370220756e6633b17fd2710e62634\
+        7d28d60b0a2d6cbb41de51740644b9fb3ba7751040121028fa937ca8cba2197a37c\
+        007176ed8941055d3bcb8627d085e94553e62f057dcc00000000";
+
     #[test]
     fn test_tx() {
-        let tx_segwit_bytes = Vec::from_hex(
-            "02000000000101595895ea20179de87052b4046dfe6fd515860505d6511a9004cf\
-            12a1f93cac7c0100000000ffffffff01deb807000000000017a9140f3444e271620\
-            c736808aa7b33e370bd87cb5a078702483045022100fb60dad8df4af2841adc0346\
-            638c16d0b8035f5e3f3753b88db122e70c79f9370220756e6633b17fd2710e62634\
-            7d28d60b0a2d6cbb41de51740644b9fb3ba7751040121028fa937ca8cba2197a37c\
-            007176ed8941055d3bcb8627d085e94553e62f057dcc00000000"
-        ).unwrap();
+        let tx_segwit_bytes = Vec::from_hex(TX_SEGWIT_HEX).unwrap();
         let tx_legacy1_bytes = Vec::from_hex(
             "ffffff7f0100000000000000000000000000000000000000000000000000000000\
             000000000000000000ffffffff0100f2052a01000000434104678afdb0fe5548271\
@@ -1004,6 +2253,70 @@ pub(crate) mod test {
         test_encoding_roundtrip(&tx_legacy2, &tx_legacy2_bytes).unwrap();
     }
 
+    #[test]
+    fn test_tx_vec_bounded() {
+        let tx_bytes = Vec::from_hex(TX_SEGWIT_HEX).unwrap();
+        let tx: Transaction = consensus::deserialize(&tx_bytes).unwrap();
+        let txs = vec![tx.clone(), tx];
+        let data = txs.strict_serialize().unwrap();
+        assert_eq!(data.len(), 2 + tx_bytes.len() * 2);
+
+        assert_eq!(
+            strict_decode_tx_vec_bounded(&data, 2, data.len()),
+            Ok(txs.clone())
+        );
+        assert_eq!(
+            strict_decode_tx_vec_bounded(&data, 1, data.len()),
+            Err(Error::ExceedMaxItems(2))
+        );
+        assert_eq!(
+            strict_decode_tx_vec_bounded(&data, 2, data.len() - 1),
+            Err(Error::DataIntegrityError(format!(
+                "transaction data exceed the limit of {} bytes",
+                data.len() - 1
+            )))
+        );
+        assert_eq!(
+            strict_decode_tx_vec_bounded(
+                &data[..data.len() - 1],
+                2,
+                data.len()
+            ),
+            Err(Error::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_annotated_tx() {
+        let tx_bytes = Vec::from_hex(TX_SEGWIT_HEX).unwrap();
+        let tx: Transaction = consensus::deserialize(&tx_bytes).unwrap();
+        let annotated = AnnotatedTx::new(tx.clone(), Amount::from_sat(1000), 3);
+
+        let mut data = tx_bytes.clone();
+        data.extend_from_slice(&1000u64.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        test_encoding_roundtrip(&annotated, &data).unwrap();
+
+        assert_eq!(annotated.tx(), &tx);
+        assert_eq!(annotated.fee(), Amount::from_sat(1000));
+        assert_eq!(annotated.ancestor_count(), 3);
+        assert_eq!(annotated.into_tx(), tx);
+    }
+
+    #[test]
+    fn test_block() {
+        let block = bitcoin::blockdata::constants::genesis_block(
+            bitcoin::Network::Regtest,
+        );
+        let block_bytes = consensus::serialize(&block);
+        let header_bytes = consensus::serialize(&block.header);
+        assert_eq!(header_bytes.len(), 80);
+        assert_eq!(block.strict_serialize().unwrap(), block_bytes);
+        assert_eq!(block.header.strict_serialize().unwrap(), header_bytes);
+        test_encoding_roundtrip(&block, &block_bytes).unwrap();
+        test_encoding_roundtrip(&block.header, &header_bytes).unwrap();
+    }
+
     #[test]
     fn test_txin() {
         let txin_bytes = Vec::from_hex(
@@ -1017,12 +2330,13 @@ pub(crate) mod test {
         test_encoding_roundtrip(&txin, &txin_bytes).unwrap();
     }
 
+    /// Serialized segwit transaction output shared by the test cases
+    const TXOUT_SEGWIT_HEX: &str =
+        "0000000000000000160014d9a1665bea770cb6ec4809943f1e8ad67a31191f";
+
     #[test]
     fn test_txout() {
-        let txout_segwit_bytes = Vec::from_hex(
-            "0000000000000000160014d9a1665bea770cb6ec4809943f1e8ad67a31191f",
-        )
-        .unwrap();
+        let txout_segwit_bytes = Vec::from_hex(TXOUT_SEGWIT_HEX).unwrap();
         let txout_legacy_bytes = Vec::from_hex(
             "000000000000000017a91413f5fb72e7a31fcac98df27c77217b02abdb47fd87",
         )
@@ -1037,27 +2351,48 @@ pub(crate) mod test {
         test_encoding_roundtrip(&txout_legacy, &txout_legacy_bytes).unwrap();
     }
 
+    #[test]
+    fn test_decode_consuming() {
+        let mut data = Vec::from_hex(TXOUT_SEGWIT_HEX).unwrap();
+        let txout_len = data.len();
+        data.extend(&0xdeadbeef_u32.to_le_bytes());
+
+        let mut cursor = io::Cursor::new(&data);
+        let (txout, consumed) =
+            strategies::BitcoinConsensus::decode_consuming::<TxOut, _>(
+                &mut cursor,
+            )
+            .unwrap();
+        let expected: TxOut =
+            consensus::deserialize(&data[..txout_len]).unwrap();
+        assert_eq!(consumed, txout_len);
+        assert_eq!(txout, expected);
+        assert_eq!(u32::strict_decode(&mut cursor).unwrap(), 0xdeadbeef);
+    }
+
+    /// Serialized PSBT shared by the PSBT test cases
+    const PSBT_HEX: &str =
+        "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566\
+        cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a91\
+        4d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a914\
+        3545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda50101000\
+        00000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f\
+        9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985fffff\
+        fff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b4\
+        0100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff020\
+        0c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac\
+        72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d0587024\
+        7304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a\
+        5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02\
+        db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e\
+        7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0\
+        c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20\
+        167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4\
+        ea169393380734464f84f2ab300000000000000";
+
     #[test]
     fn test_psbt() {
-        let psbt_bytes = Vec::from_hex(
-            "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566\
-            cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a91\
-            4d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a914\
-            3545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda50101000\
-            00000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f\
-            9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985fffff\
-            fff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b4\
-            0100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff020\
-            0c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac\
-            72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d0587024\
-            7304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a\
-            5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02\
-            db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e\
-            7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0\
-            c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20\
-            167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4\
-            ea169393380734464f84f2ab300000000000000"
-        ).unwrap();
+        let psbt_bytes = Vec::from_hex(PSBT_HEX).unwrap();
 
         let psbt: PartiallySignedTransaction =
             consensus::deserialize(&psbt_bytes).unwrap();
@@ -1065,6 +2400,297 @@ pub(crate) mod test {
         test_encoding_roundtrip(&psbt, &psbt_bytes).unwrap();
     }
 
+    #[test]
+    fn test_psbt_input_output() {
+        let psbt_bytes = Vec::from_hex(PSBT_HEX).unwrap();
+
+        let mut psbt: PartiallySignedTransaction =
+            consensus::deserialize(&psbt_bytes).unwrap();
+        let unknown_key = psbt::raw::Key {
+            type_value: 0xF0,
+            key: vec![0x01, 0x02],
+        };
+        psbt.inputs[0]
+            .unknown
+            .insert(unknown_key.clone(), vec![0xAA]);
+        psbt.outputs[1]
+            .unknown
+            .insert(unknown_key.clone(), vec![0xBB]);
+
+        for input in &psbt.inputs {
+            let mut data = vec![];
+            let map = consensus::serialize(input);
+            data.extend(&(map.len() as u16).to_le_bytes());
+            data.extend(map);
+            test_encoding_roundtrip(input, &data).unwrap();
+        }
+        for output in &psbt.outputs {
+            let mut data = vec![];
+            let map = consensus::serialize(output);
+            data.extend(&(map.len() as u16).to_le_bytes());
+            data.extend(map);
+            test_encoding_roundtrip(output, &data).unwrap();
+        }
+
+        let input = psbt::Input::strict_deserialize(
+            psbt.inputs[0].strict_serialize().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(input.unknown.get(&unknown_key), Some(&vec![0xAA]));
+        let output = psbt::Output::strict_deserialize(
+            psbt.outputs[1].strict_serialize().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(output.unknown.get(&unknown_key), Some(&vec![0xBB]));
+    }
+
+    #[test]
+    fn test_psbt_unknown_fields() {
+        let psbt_bytes = Vec::from_hex(PSBT_HEX).unwrap();
+
+        let mut psbt: PartiallySignedTransaction =
+            consensus::deserialize(&psbt_bytes).unwrap();
+
+        // Proprietary key from `0xFC` namespace
+        let proprietary_key = psbt::raw::ProprietaryKey {
+            prefix: b"test".to_vec(),
+            subtype: 0x42,
+            key: vec![0x01, 0x02, 0x03],
+        };
+        let proprietary_value = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        psbt.global
+            .proprietary
+            .insert(proprietary_key.clone(), proprietary_value.clone());
+        psbt.inputs[0]
+            .proprietary
+            .insert(proprietary_key.clone(), proprietary_value.clone());
+        psbt.outputs[0]
+            .proprietary
+            .insert(proprietary_key.clone(), proprietary_value.clone());
+
+        // Key of unknown type
+        let unknown_key = psbt::raw::Key {
+            type_value: 0xF0,
+            key: vec![0x04, 0x05],
+        };
+        let unknown_value = vec![0xCA, 0xFE];
+        psbt.global
+            .unknown
+            .insert(unknown_key.clone(), unknown_value.clone());
+        psbt.inputs[0]
+            .unknown
+            .insert(unknown_key.clone(), unknown_value.clone());
+        psbt.outputs[0]
+            .unknown
+            .insert(unknown_key.clone(), unknown_value.clone());
+
+        let data = psbt.strict_serialize().unwrap();
+        let decoded =
+            PartiallySignedTransaction::strict_deserialize(&data).unwrap();
+        assert_eq!(decoded, psbt);
+        assert_eq!(decoded.strict_serialize().unwrap(), data);
+
+        assert_eq!(
+            decoded.global.proprietary.get(&proprietary_key),
+            Some(&proprietary_value)
+        );
+        assert_eq!(
+            decoded.inputs[0].proprietary.get(&proprietary_key),
+            Some(&proprietary_value)
+        );
+        assert_eq!(
+            decoded.outputs[0].proprietary.get(&proprietary_key),
+            Some(&proprietary_value)
+        );
+        assert_eq!(
+            decoded.global.unknown.get(&unknown_key),
+            Some(&unknown_value)
+        );
+        assert_eq!(
+            decoded.inputs[0].unknown.get(&unknown_key),
+            Some(&unknown_value)
+        );
+        assert_eq!(
+            decoded.outputs[0].unknown.get(&unknown_key),
+            Some(&unknown_value)
+        );
+
+        // Raw key-value pairs must be present in the encoding verbatim
+        let raw_pair = consensus::serialize(&psbt::raw::Pair {
+            key: unknown_key,
+            value: unknown_value,
+        });
+        assert_eq!(
+            data.windows(raw_pair.len())
+                .filter(|window| *window == &raw_pair[..])
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_psbt_canonical() {
+        let psbt_bytes = Vec::from_hex(PSBT_HEX).unwrap();
+
+        let mut psbt: PartiallySignedTransaction =
+            consensus::deserialize(&psbt_bytes).unwrap();
+        let pair_a = psbt::raw::Pair {
+            key: psbt::raw::Key {
+                type_value: 0xF0,
+                key: vec![0x01],
+            },
+            value: vec![0xAA],
+        };
+        let pair_b = psbt::raw::Pair {
+            key: psbt::raw::Key {
+                type_value: 0xF0,
+                key: vec![0x02],
+            },
+            value: vec![0xBB],
+        };
+        for pair in &[&pair_a, &pair_b] {
+            psbt.global
+                .unknown
+                .insert(pair.key.clone(), pair.value.clone());
+        }
+
+        let canonical = psbt.strict_serialize().unwrap();
+        assert_eq!(strict_decode_psbt_canonical(&canonical[..]).unwrap(), psbt);
+
+        // Swapping the order of the unknown global keys
+        let mut ordered = consensus::serialize(&pair_a);
+        ordered.extend(consensus::serialize(&pair_b));
+        let mut misordered = consensus::serialize(&pair_b);
+        misordered.extend(consensus::serialize(&pair_a));
+        let pos = canonical
+            .windows(ordered.len())
+            .position(|window| window == &ordered[..])
+            .unwrap();
+        let mut noncanonical = canonical.clone();
+        noncanonical[pos..pos + ordered.len()].copy_from_slice(&misordered);
+
+        assert_eq!(
+            PartiallySignedTransaction::strict_deserialize(&noncanonical)
+                .unwrap(),
+            psbt
+        );
+        assert_eq!(
+            strict_decode_psbt_canonical(&noncanonical[..]).unwrap_err(),
+            Error::DataIntegrityError(s!("non-canonical PSBT"))
+        );
+    }
+
+    #[test]
+    fn test_witness_bounded() {
+        let witness = vec![vec![0xAAu8; 72], vec![0x02u8; 33]];
+        let data = witness.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_witness_bounded(&data[..], 2, 72).unwrap(),
+            witness
+        );
+
+        assert_eq!(
+            strict_decode_witness_bounded(&data[..], 1, 72).unwrap_err(),
+            Error::ValueOutOfRange("witness elements count", 0..2, 2)
+        );
+        assert_eq!(
+            strict_decode_witness_bounded(&data[..], 2, 71).unwrap_err(),
+            Error::ValueOutOfRange("witness element size", 0..72, 72)
+        );
+
+        // Crafted data claiming huge number of elements & huge element size
+        // without providing the data must fail before allocating
+        assert_eq!(
+            strict_decode_witness_bounded(&[0xFFu8, 0xFF][..], 100, 520)
+                .unwrap_err(),
+            Error::ValueOutOfRange("witness elements count", 0..101, 0xFFFF)
+        );
+        assert_eq!(
+            strict_decode_witness_bounded(
+                &[0x01u8, 0x00, 0xFF, 0xFF][..],
+                100,
+                520
+            )
+            .unwrap_err(),
+            Error::ValueOutOfRange("witness element size", 0..521, 0xFFFF)
+        );
+    }
+
+    #[test]
+    fn test_proprietary_field() {
+        let field = ProprietaryField {
+            prefix: b"RGB".to_vec(),
+            subtype: 0x01,
+            key: vec![0xAA],
+            value: vec![0xBB, 0xCC],
+        };
+        test_encoding_roundtrip(
+            &field,
+            [
+                0x03, 0x00, b'R', b'G', b'B', 0x01, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x01, 0x00, 0xAA, 0x02, 0x00, 0xBB, 0xCC,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_psbt_proprietary_fields() {
+        let psbt_bytes = Vec::from_hex(PSBT_HEX).unwrap();
+
+        let mut psbt: PartiallySignedTransaction =
+            consensus::deserialize(&psbt_bytes).unwrap();
+        assert!(ProprietaryField::extract_from_psbt(&psbt, b"RGB").is_empty());
+
+        psbt.global.proprietary.insert(
+            psbt::raw::ProprietaryKey {
+                prefix: b"RGB".to_vec(),
+                subtype: 0x01,
+                key: vec![0xAA],
+            },
+            vec![0xBB, 0xCC],
+        );
+        psbt.inputs[0].proprietary.insert(
+            psbt::raw::ProprietaryKey {
+                prefix: b"LNP".to_vec(),
+                subtype: 0x02,
+                key: vec![],
+            },
+            vec![0xDD],
+        );
+        psbt.outputs[1].proprietary.insert(
+            psbt::raw::ProprietaryKey {
+                prefix: b"RGB".to_vec(),
+                subtype: 0x03,
+                key: vec![],
+            },
+            vec![0xEE],
+        );
+
+        let psbt = PartiallySignedTransaction::strict_deserialize(
+            psbt.strict_serialize().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            ProprietaryField::extract_from_psbt(&psbt, b"RGB"),
+            vec![
+                ProprietaryField {
+                    prefix: b"RGB".to_vec(),
+                    subtype: 0x01,
+                    key: vec![0xAA],
+                    value: vec![0xBB, 0xCC],
+                },
+                ProprietaryField {
+                    prefix: b"RGB".to_vec(),
+                    subtype: 0x03,
+                    key: vec![],
+                    value: vec![0xEE],
+                }
+            ]
+        );
+        assert_eq!(ProprietaryField::extract_from_psbt(&psbt, b"LNP").len(), 1);
+    }
+
     #[test]
     fn test_encoding_extendedpubkey() {
         static EXT_PUBKEY1: [u8; 78] = [
@@ -1099,6 +2725,201 @@ pub(crate) mod test {
         test_encoding_roundtrip(&ext_pubkey2, &EXT_PUBKEY2).unwrap();
     }
 
+    #[test]
+    fn test_script_consensus_limited() {
+        let script = bitcoin::blockdata::script::Builder::new()
+            .push_slice(&[0xAAu8; 520])
+            .push_opcode(bitcoin::blockdata::opcodes::all::OP_DROP)
+            .into_script();
+        let data = script.strict_serialize().unwrap();
+        assert_eq!(
+            strict_decode_script_consensus_limited(&data[..]).unwrap(),
+            script
+        );
+
+        let script = bitcoin::blockdata::script::Builder::new()
+            .push_slice(&[0xAAu8; 521])
+            .into_script();
+        let data = script.strict_serialize().unwrap();
+        assert_eq!(Script::strict_deserialize(&data).unwrap(), script);
+        assert_eq!(
+            strict_decode_script_consensus_limited(&data[..]).unwrap_err(),
+            Error::DataIntegrityError(s!(
+                "script push of 521 bytes exceeds consensus limit of 520 bytes"
+            ))
+        );
+
+        let data = Script::from(vec![0x6Au8; MAX_SCRIPT_SIZE + 1])
+            .strict_serialize()
+            .unwrap();
+        assert_eq!(
+            strict_decode_script_consensus_limited(&data[..]).unwrap_err(),
+            Error::DataIntegrityError(s!(
+                "script size 10001 exceeds consensus limit of 10000 bytes"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_value_sorted_txouts() {
+        let txout = |value: u64, script: &[u8]| TxOut {
+            value,
+            script_pubkey: Script::from(script.to_vec()),
+        };
+        let txouts = ValueSortedTxOuts::new(vec![
+            txout(5000, &[0x51]),
+            txout(100, &[0x52, 0x53]),
+            txout(2500, &[]),
+            txout(100, &[0x54]),
+        ]);
+        assert_eq!(
+            txouts
+                .as_inner()
+                .iter()
+                .map(|o| o.value)
+                .collect::<Vec<_>>(),
+            vec![100, 100, 2500, 5000]
+        );
+        assert_eq!(txouts.as_inner()[0].script_pubkey[..], [0x52, 0x53]);
+
+        let data = txouts.strict_serialize().unwrap();
+        // Outputs take 8 bytes of value and `VarInt`-prefixed script
+        assert_eq!(data.len(), 2 + 4 * 12 + 11 + 10 + 9 + 10);
+        assert_eq!(&data[2..14], &[100, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[14..26], &[100, 0, 0, 0, 0, 0, 0, 0, 11, 0, 0, 0]);
+        test_encoding_roundtrip(&txouts, &data).unwrap();
+
+        assert_eq!(
+            ValueSortedTxOuts::find_by_value(&data, 2500).unwrap(),
+            Some(txout(2500, &[]))
+        );
+        assert_eq!(
+            ValueSortedTxOuts::find_by_value(&data, 100).unwrap(),
+            Some(txout(100, &[0x52, 0x53]))
+        );
+        assert_eq!(
+            ValueSortedTxOuts::find_by_value(&data, 5000).unwrap(),
+            Some(txout(5000, &[0x51]))
+        );
+        assert_eq!(ValueSortedTxOuts::find_by_value(&data, 99).unwrap(), None);
+        assert_eq!(
+            ValueSortedTxOuts::find_by_value(&data, 5001).unwrap(),
+            None
+        );
+
+        // Lookup must not decode other outputs
+        let mut corrupted = data.clone();
+        // Making script length of the last output invalid
+        let len = corrupted.len();
+        corrupted[len - 2] = 0xFF;
+        assert_eq!(
+            ValueSortedTxOuts::find_by_value(&corrupted, 100).unwrap(),
+            Some(txout(100, &[0x52, 0x53]))
+        );
+        assert!(ValueSortedTxOuts::strict_deserialize(&corrupted).is_err());
+
+        // Unsorted outputs
+        let unsorted = ValueSortedTxOuts(vec![
+            txout(5000, &[0x51]),
+            txout(100, &[0x52, 0x53]),
+        ]);
+        assert_eq!(
+            ValueSortedTxOuts::strict_deserialize(
+                unsorted.strict_serialize().unwrap()
+            ),
+            Err(Error::DataIntegrityError(s!(
+                "outputs are not sorted by value"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_encoding_large_script() {
+        let script = bitcoin::blockdata::script::Builder::new()
+            .push_slice(&[0xA5u8; 517])
+            .into_script();
+        assert_eq!(script.len(), 520);
+        let data = script.strict_serialize().unwrap();
+        assert_eq!(data, script.to_bytes().strict_serialize().unwrap());
+        test_encoding_roundtrip(&script, data).unwrap();
+    }
+
+    #[test]
+    fn test_script_dict_vec() {
+        let p2wpkh = |i: u8| {
+            Script::from(
+                Vec::from_hex(&format!(
+                    "0014{:02x}a1665bea770cb6ec4809943f1e8ad67a31191f",
+                    i
+                ))
+                .unwrap(),
+            )
+        };
+        let p2pkh = Script::from(
+            Vec::from_hex("76a914aaca991e298ab866ab60ff45221b458c7033365a88ac")
+                .unwrap(),
+        );
+        let p2sh = Script::from(
+            Vec::from_hex("a9144da34ae8199dbf684fe97af8703f12e9f7aae66287")
+                .unwrap(),
+        );
+        let mut p2wsh = vec![0x00u8, 0x20];
+        p2wsh.extend(&[0xA5u8; 32]);
+        let p2wsh = Script::from(p2wsh);
+        let op_return = Script::from(vec![0x6a, 0x02, 0xbe, 0xef]);
+
+        for (script, len) in &[
+            (p2pkh.clone(), 21),
+            (p2sh.clone(), 21),
+            (p2wpkh(0), 21),
+            (p2wsh.clone(), 33),
+        ] {
+            let compressed = CompressedScript(script.clone());
+            let data = compressed.strict_serialize().unwrap();
+            assert_eq!(data.len(), *len);
+            test_encoding_roundtrip(&compressed, data).unwrap();
+        }
+        test_encoding_roundtrip(
+            &CompressedScript(op_return.clone()),
+            [0xFF, 0x04, 0x00, 0x6a, 0x02, 0xbe, 0xef],
+        )
+        .unwrap();
+        // Standard patterns must be compressed
+        let mut data = vec![0xFF];
+        data.extend(p2wpkh(0).strict_serialize().unwrap());
+        assert_eq!(
+            CompressedScript::strict_deserialize(&data).unwrap_err(),
+            Error::DataIntegrityError(s!(
+                "standard script pattern is not compressed"
+            ))
+        );
+
+        // Block outputs with many repeated P2WPKH scripts
+        let scripts: Vec<Script> = (0..500u16)
+            .map(|i| match i % 10 {
+                0 => p2pkh.clone(),
+                1 => p2sh.clone(),
+                2 => p2wsh.clone(),
+                3 => op_return.clone(),
+                _ => p2wpkh((i % 4) as u8),
+            })
+            .collect();
+        let dict_vec = ScriptDictVec::from(scripts.clone());
+        let data = dict_vec.strict_serialize().unwrap();
+        test_encoding_roundtrip(&dict_vec, &data).unwrap();
+        assert_eq!(
+            data.len(),
+            2 + (1 + 20)
+                + (1 + 20)
+                + (1 + 32)
+                + (1 + 2 + 4)
+                + 4 * (1 + 20)
+                + 2
+                + 500 * 2
+        );
+        assert!(data.len() * 5 < scripts.strict_serialize().unwrap().len());
+    }
+
     #[test]
     fn test_encoding_script() {
         static OP_RETURN: [u8; 40] = [
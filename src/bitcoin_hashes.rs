@@ -12,11 +12,16 @@
 // You should have received a copy of the Apache 2.0 License along with this
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
+use std::io;
+
 use bitcoin_hashes::{
     hash160, hmac, ripemd160, sha256, sha256d, sha256t, sha512, Hash,
+    HashEngine,
 };
 
-use crate::{strategies, Strategy};
+use crate::{
+    strategies, strict_deserialize, Error, Strategy, StrictDecode, StrictEncode,
+};
 
 impl Strategy for sha256::Hash {
     type Strategy = strategies::HashFixedBytes;
@@ -45,3 +50,177 @@ where
 {
     type Strategy = strategies::HashFixedBytes;
 }
+
+/// Computes merkle root over the list of strict-encoded items. Each item is
+/// strict-encoded and hashed with SHA256d to form a leaf; intermediary nodes
+/// are computed as SHA256d of the concatenation of their children hashes. If
+/// a level of the tree has an odd number of nodes, the last node is
+/// duplicated, in the same way as it is done by bitcoin for transaction merkle
+/// trees. For an empty list all-zero hash is returned.
+pub fn strict_merkle_root<T>(items: &[T]) -> Result<sha256d::Hash, Error>
+where
+    T: StrictEncode,
+{
+    let mut level = items
+        .iter()
+        .map(|item| Ok(sha256d::Hash::hash(&item.strict_serialize()?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    if level.is_empty() {
+        return Ok(sha256d::Hash::default());
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let left = pair[0];
+                let right = pair.get(1).copied().unwrap_or(left);
+                let mut engine = sha256d::Hash::engine();
+                engine.input(&left[..]);
+                engine.input(&right[..]);
+                sha256d::Hash::from_engine(engine)
+            })
+            .collect();
+    }
+    Ok(level[0])
+}
+
+/// Serializes data with strict encoding and appends to them a 4-byte checksum,
+/// which is the first four bytes of SHA256d hash of the serialized data.
+/// Intended for at-rest storage of the data requiring integrity protection.
+pub fn strict_serialize_checked<T>(data: &T) -> Result<Vec<u8>, Error>
+where
+    T: StrictEncode,
+{
+    let mut payload = data.strict_serialize()?;
+    let checksum = sha256d::Hash::hash(&payload);
+    payload.extend_from_slice(&checksum[..4]);
+    Ok(payload)
+}
+
+/// Verifies checksum of the data produced by [`strict_serialize_checked`] and
+/// decodes them. Errors with [`Error::DataIntegrityError`] on checksum
+/// mismatch and with [`Error::DataNotEntirelyConsumed`] if the payload is not
+/// consumed entirely during decoding.
+pub fn strict_deserialize_checked<T>(data: impl AsRef<[u8]>) -> Result<T, Error>
+where
+    T: StrictDecode,
+{
+    let data = data.as_ref();
+    if data.len() < 4 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let (payload, checksum) = data.split_at(data.len() - 4);
+    if sha256d::Hash::hash(payload)[..4] != *checksum {
+        return Err(Error::DataIntegrityError(s!("checksum mismatch")));
+    }
+    strict_deserialize(payload)
+}
+
+/// Writer wrapper forwarding all written data to the underlying writer while
+/// computing rolling SHA256 hash over them. Allows strict-encoding sequence
+/// of records with a digest of all encoded data, which may be appended at the
+/// end for the integrity checks.
+pub struct ArchiveWriter<W: io::Write> {
+    inner: W,
+    engine: sha256::HashEngine,
+}
+
+impl<W: io::Write> ArchiveWriter<W> {
+    /// Constructs archive writer wrapping the provided writer
+    pub fn new(inner: W) -> Self {
+        ArchiveWriter {
+            inner,
+            engine: sha256::Hash::engine(),
+        }
+    }
+
+    /// Returns the underlying writer and SHA256 hash of all data written
+    pub fn finalize(self) -> (W, sha256::Hash) {
+        (self.inner, sha256::Hash::from_engine(self.engine))
+    }
+}
+
+impl<W: io::Write> io::Write for ArchiveWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.engine.input(&buf[..len]);
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn node(left: sha256d::Hash, right: sha256d::Hash) -> sha256d::Hash {
+        let mut data = left.to_vec();
+        data.extend(&right[..]);
+        sha256d::Hash::hash(&data)
+    }
+
+    #[test]
+    fn test_archive_writer() {
+        let mut writer = ArchiveWriter::new(vec![]);
+        let mut len = s!("first record").strict_encode(&mut writer).unwrap();
+        len += 0xdead_beef_u32.strict_encode(&mut writer).unwrap();
+        len += vec![1u8, 2, 3].strict_encode(&mut writer).unwrap();
+
+        let (data, digest) = writer.finalize();
+        assert_eq!(data.len(), len);
+        assert_eq!(data.len(), 2 + 12 + 4 + 2 + 3);
+        assert_eq!(digest, sha256::Hash::hash(&data));
+    }
+
+    #[test]
+    fn test_checked() {
+        let value = (s!("at rest"), 0xdead_beef_u32);
+        let data = strict_serialize_checked(&value).unwrap();
+        let payload = value.strict_serialize().unwrap();
+        assert_eq!(data.len(), payload.len() + 4);
+        assert_eq!(data[..payload.len()], payload[..]);
+        assert_eq!(data[payload.len()..], sha256d::Hash::hash(&payload)[..4]);
+        assert_eq!(
+            strict_deserialize_checked::<(String, u32)>(&data).unwrap(),
+            value
+        );
+
+        for pos in 0..data.len() {
+            let mut corrupted = data.clone();
+            corrupted[pos] ^= 0x01;
+            assert_eq!(
+                strict_deserialize_checked::<(String, u32)>(&corrupted),
+                Err(Error::DataIntegrityError(s!("checksum mismatch")))
+            );
+        }
+        assert!(strict_deserialize_checked::<u8>(&data[..3]).is_err());
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaf = |s: &str| {
+            sha256d::Hash::hash(&s.to_string().strict_serialize().unwrap())
+        };
+        let a = leaf("a");
+        let b = leaf("b");
+        let c = leaf("c");
+
+        assert_eq!(
+            strict_merkle_root::<String>(&[]).unwrap(),
+            sha256d::Hash::default()
+        );
+        assert_eq!(strict_merkle_root(&[s!("a")]).unwrap(), a);
+        assert_eq!(
+            strict_merkle_root(&[s!("a"), s!("b")]).unwrap(),
+            node(a, b)
+        );
+        assert_eq!(
+            strict_merkle_root(&[s!("a"), s!("b"), s!("c")]).unwrap(),
+            node(node(a, b), node(c, c))
+        );
+    }
+}
@@ -13,11 +13,14 @@
 // software. If not, see <https://opensource.org/licenses/Apache-2.0>.
 
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::fmt::Debug;
-use std::hash::Hash;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::io;
+use std::marker::PhantomData;
+use std::ops::{Bound, ControlFlow};
 
-use crate::{Error, StrictDecode, StrictEncode};
+use crate::primitives::{leb128_decode, leb128_encode};
+use crate::{Error, StrictDecode, StrictEncode, StrictSize};
 
 /// In terms of strict encoding, `Option` (optional values) are  
 /// represented by a *significator byte*, which MUST be either `0` (for no
@@ -53,6 +56,175 @@ where
     }
 }
 
+/// In terms of strict encoding, `Result` is represented by a single tag
+/// byte, which MUST be either `0` (for `Ok`) or `1` (for `Err`), followed by
+/// the strict encoding of the success or error value.
+impl<T, E> StrictEncode for Result<T, E>
+where
+    T: StrictEncode,
+    E: StrictEncode,
+{
+    fn strict_encode<E2: io::Write>(&self, mut e: E2) -> Result<usize, Error> {
+        Ok(match self {
+            Ok(val) => strict_encode_list!(e; 0u8, val),
+            Err(err) => strict_encode_list!(e; 1u8, err),
+        })
+    }
+}
+
+/// In terms of strict encoding, `Result` is represented by a single tag
+/// byte, which MUST be either `0` (for `Ok`) or `1` (for `Err`), followed by
+/// the strict encoding of the success or error value. Other tag values
+/// result in [`Error::EnumValueNotKnown`].
+impl<T, E> StrictDecode for Result<T, E>
+where
+    T: StrictDecode,
+    E: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        match u8::strict_decode(&mut d)? {
+            0 => Ok(Ok(T::strict_decode(&mut d)?)),
+            1 => Ok(Err(E::strict_decode(&mut d)?)),
+            tag => Err(Error::EnumValueNotKnown("Result", tag as usize)),
+        }
+    }
+}
+
+/// Error types which can be represented by a `u16` error code for the use in
+/// [`RpcResult`]
+pub trait RpcErrorCode: Sized {
+    /// Returns error code for the error
+    fn to_code(&self) -> u16;
+
+    /// Constructs error from its code, if the code is known
+    fn from_code(code: u16) -> Option<Self>;
+}
+
+/// Result of an RPC-style request, which error is strictly encoded as a
+/// `u16` error code
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RpcResult<T, E>(pub Result<T, E>);
+
+impl<T, E> From<Result<T, E>> for RpcResult<T, E> {
+    #[inline]
+    fn from(res: Result<T, E>) -> Self {
+        RpcResult(res)
+    }
+}
+
+impl<T, E> From<RpcResult<T, E>> for Result<T, E> {
+    #[inline]
+    fn from(res: RpcResult<T, E>) -> Self {
+        res.0
+    }
+}
+
+/// [`RpcResult`] is encoded in the same way as `Result<T, u16>`, where the
+/// `u16` value is the error code
+impl<T, E> StrictEncode for RpcResult<T, E>
+where
+    T: StrictEncode,
+    E: RpcErrorCode,
+{
+    #[inline]
+    fn strict_encode<E2: io::Write>(&self, e: E2) -> Result<usize, Error> {
+        self.0.as_ref().map_err(E::to_code).strict_encode(e)
+    }
+}
+
+/// [`RpcResult`] is decoded in the same way as `Result<T, u16>`, where the
+/// `u16` value is the error code. Unknown error codes result in
+/// [`Error::EnumValueNotKnown`] reporting the error type name (as given by
+/// [`core::any::type_name`]).
+impl<T, E> StrictDecode for RpcResult<T, E>
+where
+    T: StrictDecode,
+    E: RpcErrorCode,
+{
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        match Result::<T, u16>::strict_decode(d)? {
+            Ok(val) => Ok(RpcResult(Ok(val))),
+            Err(code) => E::from_code(code)
+                .map(|err| RpcResult(Err(err)))
+                .ok_or_else(|| {
+                    Error::EnumValueNotKnown(
+                        core::any::type_name::<E>(),
+                        code as usize,
+                    )
+                }),
+        }
+    }
+}
+
+/// Range bounds are represented by a single tag byte, which MUST be `0` for
+/// [`Bound::Unbounded`], `1` for [`Bound::Included`] or `2` for
+/// [`Bound::Excluded`], followed by the value strict encoding for the last
+/// two cases.
+impl<T> StrictEncode for Bound<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(match self {
+            Bound::Unbounded => strict_encode_list!(e; 0u8),
+            Bound::Included(val) => strict_encode_list!(e; 1u8, val),
+            Bound::Excluded(val) => strict_encode_list!(e; 2u8, val),
+        })
+    }
+}
+
+/// Range bounds are represented by a single tag byte, which MUST be `0` for
+/// [`Bound::Unbounded`], `1` for [`Bound::Included`] or `2` for
+/// [`Bound::Excluded`], followed by the value strict encoding for the last
+/// two cases. Other tag values result in [`Error::EnumValueNotKnown`].
+impl<T> StrictDecode for Bound<T>
+where
+    T: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        match u8::strict_decode(&mut d)? {
+            0 => Ok(Bound::Unbounded),
+            1 => Ok(Bound::Included(T::strict_decode(&mut d)?)),
+            2 => Ok(Bound::Excluded(T::strict_decode(&mut d)?)),
+            tag => Err(Error::EnumValueNotKnown("Bound", tag as usize)),
+        }
+    }
+}
+
+/// `ControlFlow` is represented by a single tag byte, which MUST be either `0`
+/// (for [`ControlFlow::Continue`]) or `1` (for [`ControlFlow::Break`]),
+/// followed by the strict encoding of the variant value.
+impl<B, C> StrictEncode for ControlFlow<B, C>
+where
+    B: StrictEncode,
+    C: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        Ok(match self {
+            ControlFlow::Continue(val) => strict_encode_list!(e; 0u8, val),
+            ControlFlow::Break(val) => strict_encode_list!(e; 1u8, val),
+        })
+    }
+}
+
+/// `ControlFlow` is represented by a single tag byte, which MUST be either `0`
+/// (for [`ControlFlow::Continue`]) or `1` (for [`ControlFlow::Break`]),
+/// followed by the strict encoding of the variant value. Other tag values
+/// result in [`Error::EnumValueNotKnown`].
+impl<B, C> StrictDecode for ControlFlow<B, C>
+where
+    B: StrictDecode,
+    C: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        match u8::strict_decode(&mut d)? {
+            0 => Ok(ControlFlow::Continue(C::strict_decode(&mut d)?)),
+            1 => Ok(ControlFlow::Break(B::strict_decode(&mut d)?)),
+            tag => Err(Error::EnumValueNotKnown("ControlFlow", tag as usize)),
+        }
+    }
+}
+
 /// In terms of strict encoding, `Vec` is stored in form of
 /// usize-encoded length (see `StrictEncode` implementation for `usize`
 /// type for encoding platform-independent constant-length
@@ -279,6 +451,29 @@ where
     }
 }
 
+/// Decodes map encoded as `BTreeMap<K, V>` or `HashMap<usize, V>` without
+/// constructing the map in memory: the provided callback `f` is called for
+/// each of the decoded entries in their encoding order. Decoding stops on the
+/// first error returned by the callback.
+pub fn strict_decode_map_streaming<K, V, D, F>(
+    mut d: D,
+    mut f: F,
+) -> Result<(), Error>
+where
+    K: StrictDecode,
+    V: StrictDecode,
+    D: io::Read,
+    F: FnMut(K, V) -> Result<(), Error>,
+{
+    let len = usize::strict_decode(&mut d)?;
+    for _ in 0..len {
+        let key = K::strict_decode(&mut d)?;
+        let val = V::strict_decode(&mut d)?;
+        f(key, val)?;
+    }
+    Ok(())
+}
+
 /// Two-component tuples are encoded as they were fields in the parent
 /// data structure
 impl<K, V> StrictEncode for (K, V)
@@ -305,130 +500,1573 @@ where
     }
 }
 
-#[cfg(test)]
-pub mod test {
-    use super::*;
-    use crate::strict_serialize;
+impl<T> StrictSize for Option<T>
+where
+    T: StrictSize,
+{
+    #[inline]
+    fn strict_size(&self) -> usize {
+        1 + self.as_ref().map(T::strict_size).unwrap_or_default()
+    }
+}
 
-    /// Test for checking the following rule from LNPBP-5:
-    ///
-    /// `Option<T>` of any type T, which are set to `Option::None` value MUST
-    /// encode as two zero bytes and it MUST be possible to decode optional
-    /// of any type from two zero bytes which MUST result in `Option::None`
-    /// value.
-    #[test]
-    fn test_option_encode_none() {
-        let o1: Option<u8> = None;
-        let o2: Option<u64> = None;
+impl<T, E> StrictSize for Result<T, E>
+where
+    T: StrictSize,
+    E: StrictSize,
+{
+    #[inline]
+    fn strict_size(&self) -> usize {
+        1 + match self {
+            Ok(val) => val.strict_size(),
+            Err(err) => err.strict_size(),
+        }
+    }
+}
 
-        let two_zero_bytes = &vec![0u8][..];
+impl<T> StrictSize for Vec<T>
+where
+    T: StrictSize,
+{
+    fn strict_size(&self) -> usize {
+        self.len().strict_size()
+            + self.iter().map(T::strict_size).sum::<usize>()
+    }
+}
 
-        assert_eq!(strict_serialize(&o1).unwrap(), two_zero_bytes);
-        assert_eq!(strict_serialize(&o2).unwrap(), two_zero_bytes);
+impl<T> StrictSize for HashSet<T>
+where
+    T: StrictSize + Eq + Ord + Hash + Debug,
+{
+    fn strict_size(&self) -> usize {
+        self.len().strict_size()
+            + self.iter().map(T::strict_size).sum::<usize>()
+    }
+}
 
-        assert_eq!(Option::<u8>::strict_decode(two_zero_bytes).unwrap(), None);
-        assert_eq!(Option::<u64>::strict_decode(two_zero_bytes).unwrap(), None);
+impl<T> StrictSize for BTreeSet<T>
+where
+    T: StrictSize + Eq + Ord + Debug,
+{
+    fn strict_size(&self) -> usize {
+        self.len().strict_size()
+            + self.iter().map(T::strict_size).sum::<usize>()
     }
+}
 
-    /// Test for checking the following rule from LNPBP-5:
-    ///
-    /// `Option<T>` of any type T, which are set to `Option::Some<T>` value MUST
-    /// encode as a `Vec<T>` structure containing a single item equal to the
-    /// `Option::unwrap()` value.
-    #[test]
-    fn test_option_encode_some() {
-        let o1: Option<u8> = Some(0);
-        let o2: Option<u8> = Some(13);
-        let o3: Option<u8> = Some(0xFF);
-        let o4: Option<u64> = Some(13);
-        let o5: Option<u64> = Some(0x1FF);
-        let o6: Option<u64> = Some(0xFFFFFFFFFFFFFFFF);
-        let o7: Option<usize> = Some(13);
-        let o8: Option<usize> = Some(0xFFFFFFFFFFFFFFFF);
+impl<T> StrictSize for HashMap<usize, T>
+where
+    T: StrictSize + Clone,
+{
+    fn strict_size(&self) -> usize {
+        self.len().strict_size()
+            + self
+                .iter()
+                .map(|(key, val)| key.strict_size() + val.strict_size())
+                .sum::<usize>()
+    }
+}
 
-        let byte_0 = &[1u8, 0u8][..];
-        let byte_13 = &[1u8, 13u8][..];
-        let byte_255 = &[1u8, 0xFFu8][..];
-        let word_13 = &[1u8, 13u8, 0u8][..];
-        let qword_13 = &[1u8, 13u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8][..];
-        let qword_256 =
-            &[1u8, 0xFFu8, 0x01u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8][..];
-        let qword_max = &[
-            1u8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
-        ][..];
+impl<K, V> StrictSize for BTreeMap<K, V>
+where
+    K: StrictSize + Ord + Clone,
+    V: StrictSize + Clone,
+{
+    fn strict_size(&self) -> usize {
+        self.len().strict_size()
+            + self
+                .iter()
+                .map(|(key, val)| key.strict_size() + val.strict_size())
+                .sum::<usize>()
+    }
+}
 
-        assert_eq!(strict_serialize(&o1).unwrap(), byte_0);
-        assert_eq!(strict_serialize(&o2).unwrap(), byte_13);
-        assert_eq!(strict_serialize(&o3).unwrap(), byte_255);
-        assert_eq!(strict_serialize(&o4).unwrap(), qword_13);
-        assert_eq!(strict_serialize(&o5).unwrap(), qword_256);
-        assert_eq!(strict_serialize(&o6).unwrap(), qword_max);
-        assert_eq!(strict_serialize(&o7).unwrap(), word_13);
-        assert!(strict_serialize(&o8).err().is_some());
+impl<K, V> StrictSize for (K, V)
+where
+    K: StrictSize + Clone,
+    V: StrictSize + Clone,
+{
+    const LEN: Option<usize> = match (K::LEN, V::LEN) {
+        (Some(k), Some(v)) => Some(k + v),
+        _ => None,
+    };
 
-        assert_eq!(Option::<u8>::strict_decode(byte_0).unwrap(), Some(0));
-        assert_eq!(Option::<u8>::strict_decode(byte_13).unwrap(), Some(13));
-        assert_eq!(Option::<u8>::strict_decode(byte_255).unwrap(), Some(0xFF));
-        assert_eq!(Option::<u64>::strict_decode(qword_13).unwrap(), Some(13));
-        assert_eq!(
-            Option::<u64>::strict_decode(qword_256).unwrap(),
-            Some(0x1FF)
-        );
-        assert_eq!(
-            Option::<u64>::strict_decode(qword_max).unwrap(),
-            Some(0xFFFFFFFFFFFFFFFF)
-        );
-        assert_eq!(Option::<usize>::strict_decode(word_13).unwrap(), Some(13));
-        assert_eq!(
-            Option::<usize>::strict_decode(qword_max).unwrap(),
-            Some(0xFFFF)
-        );
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.0.strict_size() + self.1.strict_size()
     }
+}
 
-    /// Test trying decoding of non-zero and non-single item vector structures,
-    /// which MUST fail with a specific error.
-    #[test]
-    fn test_option_decode_vec() {
-        assert!(Option::<u8>::strict_decode(&[2u8, 0u8, 0u8, 0u8][..])
-            .err()
-            .is_some());
-        assert!(Option::<u8>::strict_decode(&[3u8, 0u8, 0u8, 0u8][..])
-            .err()
-            .is_some());
-        assert!(Option::<u8>::strict_decode(&[0xFFu8, 0u8, 0u8, 0u8][..])
-            .err()
-            .is_some());
+/// Vector of boolean values, which is strictly encoded in bit-packed form
+/// (unlike `Vec<bool>`, which uses a byte per each value).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BitVec(Vec<bool>);
+
+impl BitVec {
+    /// Constructs empty bit vector
+    #[inline]
+    pub fn new() -> Self {
+        BitVec(vec![])
     }
 
-    /// Test for checking the following rule from LNPBP-5:
-    ///
-    /// Array of any commitment-serializable type T MUST contain strictly less
-    /// than `0x10000` items and must encode as 16-bit little-endian value
-    /// corresponding to the number of items followed by a direct encoding
-    /// of each of the items.
-    #[test]
-    fn test_vec_encode() {
-        let v1: Vec<u8> = vec![0, 13, 0xFF];
-        let v2: Vec<u8> = vec![13];
-        let v3: Vec<u64> = vec![0, 13, 13, 0x1FF, 0xFFFFFFFFFFFFFFFF];
-        let v4: Vec<u8> =
-            (0..0x1FFFF).map(|item| (item % 0xFF) as u8).collect();
+    /// Returns reference to the underlying `Vec<bool>`
+    #[inline]
+    pub fn as_inner(&self) -> &Vec<bool> {
+        &self.0
+    }
 
-        let s1 = &[3u8, 0u8, 0u8, 13u8, 0xFFu8][..];
-        let s2 = &[1u8, 0u8, 13u8][..];
-        let s3 = &[
-            5u8, 0u8, 0, 0, 0, 0, 0, 0, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0, 13, 0,
-            0, 0, 0, 0, 0, 0, 0xFF, 1, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF,
-            0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-        ][..];
+    /// Returns mutable reference to the underlying `Vec<bool>`
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut Vec<bool> {
+        &mut self.0
+    }
 
-        assert_eq!(strict_serialize(&v1).unwrap(), s1);
-        assert_eq!(strict_serialize(&v2).unwrap(), s2);
-        assert_eq!(strict_serialize(&v3).unwrap(), s3);
-        assert!(strict_serialize(&v4).err().is_some());
+    /// Converts into the underlying `Vec<bool>`
+    #[inline]
+    pub fn into_inner(self) -> Vec<bool> {
+        self.0
+    }
+}
 
-        assert_eq!(Vec::<u8>::strict_decode(s1).unwrap(), v1);
-        assert_eq!(Vec::<u8>::strict_decode(s2).unwrap(), v2);
-        assert_eq!(Vec::<u64>::strict_decode(s3).unwrap(), v3);
+impl From<Vec<bool>> for BitVec {
+    #[inline]
+    fn from(bits: Vec<bool>) -> Self {
+        BitVec(bits)
+    }
+}
+
+impl From<BitVec> for Vec<bool> {
+    #[inline]
+    fn from(bits: BitVec) -> Self {
+        bits.0
+    }
+}
+
+/// [`BitVec`] is encoded as `usize`-encoded number of bits, followed by
+/// `ceil(len / 8)` bytes with bits packed starting from the least significant
+/// one. Unused (padding) bits of the last byte are set to zero.
+impl StrictEncode for BitVec {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let mut encoded = self.0.len().strict_encode(&mut e)?;
+        let bytes =
+            self.0
+                .chunks(8)
+                .map(|chunk| {
+                    chunk.iter().enumerate().fold(0u8, |byte, (no, bit)| {
+                        byte | ((*bit as u8) << no)
+                    })
+                })
+                .collect::<Vec<u8>>();
+        e.write_all(&bytes)?;
+        encoded += bytes.len();
+        Ok(encoded)
+    }
+}
+
+/// [`BitVec`] is decoded from `usize`-encoded number of bits, followed by
+/// `ceil(len / 8)` bytes with bits packed starting from the least significant
+/// one. Values of the padding bits in the last byte are ignored.
+impl StrictDecode for BitVec {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut d)?;
+        let mut bytes = vec![0u8; (len + 7) / 8];
+        d.read_exact(&mut bytes)?;
+        let bits = (0..len)
+            .map(|no| bytes[no / 8] & (1 << (no % 8)) != 0)
+            .collect();
+        Ok(BitVec(bits))
+    }
+}
+
+/// Map from `u64` keys to arbitrary values, which keys are encoded as
+/// deltas from the previous key (starting with zero) using LEB128
+/// variable-length integer encoding. Allows compact representation of maps
+/// with large, but densely-located keys (like block heights or timestamps).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DeltaMap<V>(BTreeMap<u64, V>);
+
+impl<V> DeltaMap<V> {
+    /// Constructs empty map
+    #[inline]
+    pub fn new() -> Self {
+        DeltaMap(BTreeMap::new())
+    }
+
+    /// Returns reference to the underlying `BTreeMap`
+    #[inline]
+    pub fn as_inner(&self) -> &BTreeMap<u64, V> {
+        &self.0
+    }
+
+    /// Returns mutable reference to the underlying `BTreeMap`
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut BTreeMap<u64, V> {
+        &mut self.0
+    }
+
+    /// Converts into the underlying `BTreeMap`
+    #[inline]
+    pub fn into_inner(self) -> BTreeMap<u64, V> {
+        self.0
+    }
+}
+
+impl<V> From<BTreeMap<u64, V>> for DeltaMap<V> {
+    #[inline]
+    fn from(map: BTreeMap<u64, V>) -> Self {
+        DeltaMap(map)
+    }
+}
+
+impl<V> From<DeltaMap<V>> for BTreeMap<u64, V> {
+    #[inline]
+    fn from(map: DeltaMap<V>) -> Self {
+        map.0
+    }
+}
+
+/// Strict encoding of the [`DeltaMap`] starts with `u16` number of items,
+/// followed by items in ascending key order. Each item is represented by a
+/// LEB128-encoded difference between its key and the key of the previous
+/// item (or zero for the first item), followed by the strict encoding of the
+/// value.
+impl<V> StrictEncode for DeltaMap<V>
+where
+    V: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let len = self.0.len() as usize;
+        let encoded = len.strict_encode(&mut e)?;
+
+        let mut prev = 0u64;
+        self.0.iter().try_fold(encoded, |mut acc, (key, val)| {
+            acc += leb128_encode(key - prev, &mut e)?;
+            acc += val.strict_encode(&mut e)?;
+            prev = *key;
+            Ok(acc)
+        })
+    }
+}
+
+/// Strict decoding of the [`DeltaMap`] fails with [`Error::RepeatedValue`]
+/// if a zero delta is met for any item other than the first one, and with
+/// [`Error::DataIntegrityError`] if the accumulated key overflows `u64`.
+impl<V> StrictDecode for DeltaMap<V>
+where
+    V: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut d)?;
+        let mut map = BTreeMap::<u64, V>::new();
+        let mut prev = 0u64;
+        for no in 0..len {
+            let delta = leb128_decode(&mut d)?;
+            if no > 0 && delta == 0 {
+                return Err(Error::RepeatedValue(format!("{:?}", prev)));
+            }
+            let key = prev.checked_add(delta).ok_or_else(|| {
+                Error::DataIntegrityError(s!(
+                    "delta-encoded map key overflows u64"
+                ))
+            })?;
+            let val = V::strict_decode(&mut d)?;
+            map.insert(key, val);
+            prev = key;
+        }
+        Ok(DeltaMap(map))
+    }
+}
+
+/// Computes CRC-32 (IEEE 802.3 polynomial) checksum of the provided data
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Map from `String` keys to arbitrary values, which keys are front-coded:
+/// each key is represented by the length of the prefix it shares with the
+/// previous key plus the remaining suffix. Allows compact representation of
+/// maps with many keys sharing common prefixes (like hierarchical
+/// configuration keys).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct PrefixCompressedMap<V>(BTreeMap<String, V>);
+
+impl<V> PrefixCompressedMap<V> {
+    /// Constructs empty map
+    #[inline]
+    pub fn new() -> Self {
+        PrefixCompressedMap(BTreeMap::new())
+    }
+
+    /// Returns reference to the underlying `BTreeMap`
+    #[inline]
+    pub fn as_inner(&self) -> &BTreeMap<String, V> {
+        &self.0
+    }
+
+    /// Returns mutable reference to the underlying `BTreeMap`
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut BTreeMap<String, V> {
+        &mut self.0
+    }
+
+    /// Converts into the underlying `BTreeMap`
+    #[inline]
+    pub fn into_inner(self) -> BTreeMap<String, V> {
+        self.0
+    }
+}
+
+impl<V> From<BTreeMap<String, V>> for PrefixCompressedMap<V> {
+    #[inline]
+    fn from(map: BTreeMap<String, V>) -> Self {
+        PrefixCompressedMap(map)
+    }
+}
+
+impl<V> From<PrefixCompressedMap<V>> for BTreeMap<String, V> {
+    #[inline]
+    fn from(map: PrefixCompressedMap<V>) -> Self {
+        map.0
+    }
+}
+
+/// Returns length (in bytes) of the longest common prefix of two strings
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map(|((pos, _), _)| pos)
+        .unwrap_or_else(|| a.len().min(b.len()))
+}
+
+/// Strict encoding of the [`PrefixCompressedMap`] starts with `u16` number
+/// of items, followed by items in ascending key order. Each item is
+/// represented by a LEB128-encoded length (in bytes) of the longest prefix
+/// the key shares with the previous key (zero for the first item), followed
+/// by the strict encoding of the rest of the key as a `String` and the strict
+/// encoding of the value.
+impl<V> StrictEncode for PrefixCompressedMap<V>
+where
+    V: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let encoded = self.0.len().strict_encode(&mut e)?;
+
+        let mut prev = "";
+        self.0.iter().try_fold(encoded, |mut acc, (key, val)| {
+            let shared = common_prefix_len(prev, key);
+            acc += leb128_encode(shared as u64, &mut e)?;
+            acc += (&key[shared..]).strict_encode(&mut e)?;
+            acc += val.strict_encode(&mut e)?;
+            prev = key;
+            Ok(acc)
+        })
+    }
+}
+
+/// Strict decoding of the [`PrefixCompressedMap`] fails with
+/// [`Error::RepeatedValue`] if the keys are repeated and with
+/// [`Error::DataIntegrityError`] if they are not sorted or the shared prefix
+/// length does not correspond to the longest common prefix with the previous
+/// key.
+impl<V> StrictDecode for PrefixCompressedMap<V>
+where
+    V: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut d)?;
+        let mut map = BTreeMap::<String, V>::new();
+        let mut prev = String::new();
+        for no in 0..len {
+            let shared = leb128_decode(&mut d)?;
+            let suffix = String::strict_decode(&mut d)?;
+            if shared > prev.len() as u64
+                || !prev.is_char_boundary(shared as usize)
+            {
+                return Err(Error::DataIntegrityError(s!(
+                    "invalid shared prefix length in prefix-compressed map"
+                )));
+            }
+            let key = format!("{}{}", &prev[..shared as usize], suffix);
+            if no > 0 && key == prev {
+                return Err(Error::RepeatedValue(key));
+            }
+            if (no > 0 && key < prev)
+                || common_prefix_len(&prev, &key) != shared as usize
+            {
+                return Err(Error::DataIntegrityError(s!(
+                    "non-canonical prefix-compressed map encoding"
+                )));
+            }
+            let val = V::strict_decode(&mut d)?;
+            map.insert(key.clone(), val);
+            prev = key;
+        }
+        Ok(PrefixCompressedMap(map))
+    }
+}
+
+/// Vector which is strictly encoded using dictionary encoding: each distinct
+/// value is stored only once, and the vector itself is represented by `u16`
+/// indexes into the dictionary. Allows compact representation of vectors with
+/// low cardinality of large values (like repeated addresses).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DictVec<T>(Vec<T>)
+where
+    T: Eq + Hash;
+
+impl<T> DictVec<T>
+where
+    T: Eq + Hash,
+{
+    /// Constructs empty vector
+    #[inline]
+    pub fn new() -> Self {
+        DictVec(Vec::new())
+    }
+
+    /// Returns reference to the underlying `Vec`
+    #[inline]
+    pub fn as_inner(&self) -> &Vec<T> {
+        &self.0
+    }
+
+    /// Returns mutable reference to the underlying `Vec`
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+
+    /// Converts into the underlying `Vec`
+    #[inline]
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for DictVec<T>
+where
+    T: Eq + Hash,
+{
+    #[inline]
+    fn from(vec: Vec<T>) -> Self {
+        DictVec(vec)
+    }
+}
+
+impl<T> From<DictVec<T>> for Vec<T>
+where
+    T: Eq + Hash,
+{
+    #[inline]
+    fn from(vec: DictVec<T>) -> Self {
+        vec.0
+    }
+}
+
+/// Strict encoding of the [`DictVec`] starts with the dictionary of distinct
+/// values, encoded as `Vec<T>` with the values ordered by their first
+/// occurrence in the vector, followed by `u16` number of items and `u16`
+/// dictionary index for each of the items. Fails with
+/// [`Error::ExceedMaxItems`] if the number of distinct values exceeds
+/// `u16::MAX`.
+impl<T> StrictEncode for DictVec<T>
+where
+    T: StrictEncode + Eq + Hash,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let mut dict = Vec::<&T>::new();
+        let mut lookup = HashMap::<&T, u16>::new();
+        let mut indexes = Vec::with_capacity(self.0.len());
+        for item in &self.0 {
+            let index = match lookup.get(item) {
+                Some(index) => *index,
+                None => {
+                    if dict.len() >= u16::MAX as usize {
+                        return Err(Error::ExceedMaxItems(dict.len() + 1));
+                    }
+                    let index = dict.len() as u16;
+                    dict.push(item);
+                    lookup.insert(item, index);
+                    index
+                }
+            };
+            indexes.push(index);
+        }
+
+        let mut len = dict.len().strict_encode(&mut e)?;
+        for item in dict {
+            len += item.strict_encode(&mut e)?;
+        }
+        Ok(len + indexes.strict_encode(&mut e)?)
+    }
+}
+
+/// Strict decoding of the [`DictVec`] fails with [`Error::RepeatedValue`] if
+/// the dictionary contains repeated values, with [`Error::ValueOutOfRange`]
+/// for indexes outside of the dictionary and with
+/// [`Error::DataIntegrityError`] if the dictionary values are not ordered by
+/// their first occurrence in the vector or some of them are not used.
+impl<T> StrictDecode for DictVec<T>
+where
+    T: StrictDecode + Eq + Hash + Clone + Debug,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let dict = Vec::<T>::strict_decode(&mut d)?;
+        let mut distinct = HashSet::<&T>::with_capacity(dict.len());
+        for item in &dict {
+            if !distinct.insert(item) {
+                return Err(Error::RepeatedValue(format!("{:?}", item)));
+            }
+        }
+
+        let len = usize::strict_decode(&mut d)?;
+        let mut vec = Vec::with_capacity(len);
+        let mut used = 0usize;
+        for _ in 0..len {
+            let index = u16::strict_decode(&mut d)? as usize;
+            if index >= dict.len() {
+                return Err(Error::ValueOutOfRange(
+                    "DictVec index",
+                    0..dict.len() as u128,
+                    index as u128,
+                ));
+            }
+            if index > used {
+                return Err(Error::DataIntegrityError(s!(
+                    "non-canonical DictVec encoding"
+                )));
+            }
+            if index == used {
+                used += 1;
+            }
+            vec.push(dict[index].clone());
+        }
+        if used != dict.len() {
+            return Err(Error::DataIntegrityError(s!(
+                "non-canonical DictVec encoding"
+            )));
+        }
+        Ok(DictVec(vec))
+    }
+}
+
+/// Vector which is strictly encoded as a sequence of fixed-size chunks, each
+/// of them protected with a CRC-32 checksum. This allows detecting and
+/// skipping corrupted parts of large append-only data structures (like logs)
+/// with [`ChunkedVec::strict_decode_lenient`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ChunkedVec<T> {
+    items: Vec<T>,
+    chunk_size: u16,
+}
+
+impl<T> ChunkedVec<T> {
+    /// Constructs chunked vector from the provided items using given number
+    /// of items per chunk.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero
+    pub fn new(items: Vec<T>, chunk_size: u16) -> Self {
+        assert_ne!(chunk_size, 0, "ChunkedVec chunk size must be non-zero");
+        ChunkedVec { items, chunk_size }
+    }
+
+    /// Returns items stored in the vector
+    #[inline]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns maximal number of items per chunk
+    #[inline]
+    pub fn chunk_size(&self) -> u16 {
+        self.chunk_size
+    }
+
+    /// Converts into the vector of stored items
+    #[inline]
+    pub fn into_inner(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> ChunkedVec<T>
+where
+    T: StrictDecode,
+{
+    /// Reads a single chunk, returning `Ok(None)` if the chunk data are
+    /// corrupted, or an error if the chunk itself can't be read from the
+    /// stream. A corrupted chunk length prefix is also an error, since the
+    /// position of the following chunks can't be known. To keep the encoding
+    /// deterministic, all chunks except the last one must hold exactly
+    /// `chunk_size` items, and the last one must be non-empty; other chunks
+    /// are treated as corrupted.
+    fn decode_chunk<D: io::Read>(
+        mut d: D,
+        index: usize,
+        chunk_size: u16,
+        is_last: bool,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let len = u16::strict_decode(&mut d)?;
+        if u16::strict_decode(&mut d)? != !len {
+            return Err(Error::DataIntegrityError(format!(
+                "ChunkedVec chunk #{} length prefix is corrupted",
+                index
+            )));
+        }
+        let mut payload = vec![0u8; len as usize];
+        d.read_exact(&mut payload)?;
+        let checksum = u32::strict_decode(&mut d)?;
+        if chunk_crc32(len, &payload) != checksum {
+            return Ok(None);
+        }
+        match crate::strict_deserialize::<Vec<T>>(&payload) {
+            Ok(chunk) if chunk.len() == chunk_size as usize => Ok(Some(chunk)),
+            Ok(chunk)
+                if is_last
+                    && !chunk.is_empty()
+                    && chunk.len() < chunk_size as usize =>
+            {
+                Ok(Some(chunk))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads chunk size, failing if it is zero
+    fn decode_chunk_size<D: io::Read>(d: D) -> Result<u16, Error> {
+        match u16::strict_decode(d)? {
+            0 => {
+                Err(Error::DataIntegrityError(s!("zero ChunkedVec chunk size")))
+            }
+            chunk_size => Ok(chunk_size),
+        }
+    }
+
+    /// Decodes chunked vector skipping all chunks which data are corrupted.
+    /// Returns recovered items and a list of indexes of the corrupted chunks.
+    /// Errors are returned only if the chunk structure itself can't be read
+    /// (for instance, because of I/O errors, unexpected end of data, zero
+    /// chunk size or a corrupted chunk length prefix).
+    pub fn strict_decode_lenient<D: io::Read>(
+        mut d: D,
+    ) -> Result<(Vec<T>, Vec<usize>), Error> {
+        let chunk_size = Self::decode_chunk_size(&mut d)?;
+        let count = usize::strict_decode(&mut d)?;
+        let mut items = Vec::new();
+        let mut corrupted = Vec::new();
+        for index in 0..count {
+            let is_last = index + 1 == count;
+            match Self::decode_chunk(&mut d, index, chunk_size, is_last)? {
+                Some(chunk) => items.extend(chunk),
+                None => corrupted.push(index),
+            }
+        }
+        Ok((items, corrupted))
+    }
+}
+
+/// Computes checksum of a [`ChunkedVec`] chunk, which covers both the chunk
+/// length prefix and its data
+fn chunk_crc32(len: u16, payload: &[u8]) -> u32 {
+    let mut data = Vec::with_capacity(2 + payload.len());
+    data.extend_from_slice(&len.to_le_bytes());
+    data.extend_from_slice(payload);
+    crc32(&data)
+}
+
+/// Strict encoding of the [`ChunkedVec`] starts with `u16` chunk size and
+/// number of chunks, followed by the chunks. Each chunk is represented by a
+/// byte string holding strict encoding of the chunk items as a `Vec<T>`:
+/// `u16` length of the byte string, its one's complement (protecting the
+/// length, like in DEFLATE stored blocks), the byte string itself and 4-byte
+/// CRC-32 checksum of the length followed by the byte string.
+impl<T> StrictEncode for ChunkedVec<T>
+where
+    T: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let chunks = self.items.chunks(self.chunk_size as usize);
+        let mut encoded = self.chunk_size.strict_encode(&mut e)?;
+        encoded += chunks.len().strict_encode(&mut e)?;
+        for chunk in chunks {
+            let mut payload = vec![];
+            chunk.len().strict_encode(&mut payload)?;
+            for item in chunk {
+                item.strict_encode(&mut payload)?;
+            }
+            let len = payload.len();
+            if len > u16::MAX as usize {
+                return Err(Error::ExceedMaxItems(len));
+            }
+            let len = len as u16;
+            encoded += len.strict_encode(&mut e)?;
+            encoded += (!len).strict_encode(&mut e)?;
+            e.write_all(&payload)?;
+            encoded += payload.len();
+            encoded += chunk_crc32(len, &payload).strict_encode(&mut e)?;
+        }
+        Ok(encoded)
+    }
+}
+
+/// Strict decoding of the [`ChunkedVec`] fails with
+/// [`Error::DataIntegrityError`] on the first corrupted chunk; use
+/// [`ChunkedVec::strict_decode_lenient`] to skip such chunks. Chunks which
+/// are not full (except the last one) or empty are considered corrupted,
+/// so each vector has a single valid encoding.
+impl<T> StrictDecode for ChunkedVec<T>
+where
+    T: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let chunk_size = Self::decode_chunk_size(&mut d)?;
+        let count = usize::strict_decode(&mut d)?;
+        let mut items = Vec::new();
+        for index in 0..count {
+            let is_last = index + 1 == count;
+            match Self::decode_chunk(&mut d, index, chunk_size, is_last)? {
+                Some(chunk) => items.extend(chunk),
+                None => {
+                    return Err(Error::DataIntegrityError(format!(
+                        "ChunkedVec chunk #{} is corrupted",
+                        index
+                    )))
+                }
+            }
+        }
+        Ok(ChunkedVec { items, chunk_size })
+    }
+}
+
+/// Defines which record types of a [`TlvStream`] are known to (i.e. can be
+/// interpreted by) the parser. Following the "it's ok to be odd" rule,
+/// unknown records of even types are rejected during decoding, while unknown
+/// records of odd types are retained without interpretation.
+pub trait TlvSchema {
+    /// List of known record types
+    const KNOWN_TYPES: &'static [u64];
+
+    /// Detects whether a record type is known to the parser
+    #[inline]
+    fn is_known(ty: u64) -> bool {
+        Self::KNOWN_TYPES.contains(&ty)
+    }
+}
+
+/// Stream of type-length-value (TLV) records, allowing adding new fields to
+/// the data structures without breaking older parsers. Records are kept
+/// sorted by their type, and the set of record types understood by the
+/// parser is defined by the [`TlvSchema`] type parameter.
+pub struct TlvStream<S>
+where
+    S: TlvSchema,
+{
+    records: BTreeMap<u64, Vec<u8>>,
+    schema: PhantomData<S>,
+}
+
+// Schema is a marker type, so the traits below are implemented manually to
+// avoid requiring them from the schema type (as derive macros would do)
+
+impl<S> Clone for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        TlvStream {
+            records: self.records.clone(),
+            schema: PhantomData,
+        }
+    }
+}
+
+impl<S> PartialEq for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.records == other.records
+    }
+}
+
+impl<S> Eq for TlvStream<S> where S: TlvSchema {}
+
+impl<S> Hash for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.records.hash(state)
+    }
+}
+
+impl<S> Debug for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlvStream")
+            .field("records", &self.records)
+            .finish()
+    }
+}
+
+impl<S> Default for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    #[inline]
+    fn default() -> Self {
+        TlvStream::new()
+    }
+}
+
+impl<S> TlvStream<S>
+where
+    S: TlvSchema,
+{
+    /// Constructs empty TLV stream
+    #[inline]
+    pub fn new() -> Self {
+        TlvStream {
+            records: BTreeMap::new(),
+            schema: PhantomData,
+        }
+    }
+
+    /// Returns number of records in the stream
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Detects whether the stream has no records
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns value of the record with the given type, if present
+    #[inline]
+    pub fn get(&self, ty: u64) -> Option<&[u8]> {
+        self.records.get(&ty).map(Vec::as_slice)
+    }
+
+    /// Adds record to the stream, returning the value of the previously
+    /// present record with the same type, if any. Writers must not add
+    /// records of even types which are not known to the schema, since such
+    /// streams will fail to decode.
+    #[inline]
+    pub fn insert(&mut self, ty: u64, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.records.insert(ty, value)
+    }
+
+    /// Removes record with the given type from the stream, returning its
+    /// value
+    #[inline]
+    pub fn remove(&mut self, ty: u64) -> Option<Vec<u8>> {
+        self.records.remove(&ty)
+    }
+
+    /// Iterates over all records in ascending type order
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.records
+            .iter()
+            .map(|(ty, value)| (*ty, value.as_slice()))
+    }
+
+    /// Iterates over records which types are not known to the schema (and
+    /// which were skipped during decoding)
+    #[inline]
+    pub fn unknown(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.iter().filter(|(ty, _)| !S::is_known(*ty))
+    }
+}
+
+/// Strict encoding of the [`TlvStream`] starts with `u16` number of records,
+/// followed by records in strictly ascending type order. Each record is
+/// represented by LEB128-encoded type and value length, followed by the
+/// value bytes.
+impl<S> StrictEncode for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let encoded = self.records.len().strict_encode(&mut e)?;
+        self.records
+            .iter()
+            .try_fold(encoded, |mut acc, (ty, value)| {
+                acc += leb128_encode(*ty, &mut e)?;
+                acc += leb128_encode(value.len() as u64, &mut e)?;
+                e.write_all(value)?;
+                Ok(acc + value.len())
+            })
+    }
+}
+
+/// Strict decoding of the [`TlvStream`] fails with [`Error::RepeatedValue`]
+/// if a record type is repeated, and with [`Error::DataIntegrityError`] if
+/// the records are not sorted by their type, type or length are not
+/// minimally encoded, or a record of unknown even type is met.
+impl<S> StrictDecode for TlvStream<S>
+where
+    S: TlvSchema,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = usize::strict_decode(&mut d)?;
+        let mut records = BTreeMap::<u64, Vec<u8>>::new();
+        let mut prev = 0u64;
+        for no in 0..len {
+            let ty = leb128_decode(&mut d)?;
+            if no > 0 && ty == prev {
+                return Err(Error::RepeatedValue(format!("{:?}", ty)));
+            }
+            if no > 0 && ty < prev {
+                return Err(Error::DataIntegrityError(s!(
+                    "TLV records are not in strictly ascending type order"
+                )));
+            }
+            if ty % 2 == 0 && !S::is_known(ty) {
+                return Err(Error::DataIntegrityError(format!(
+                    "unknown even TLV record type {}",
+                    ty
+                )));
+            }
+            let value_len = leb128_decode(&mut d)?;
+            let mut value = vec![];
+            io::Read::read_to_end(
+                &mut io::Read::take(&mut d, value_len),
+                &mut value,
+            )?;
+            if value.len() as u64 != value_len {
+                return Err(Error::UnexpectedEof);
+            }
+            records.insert(ty, value);
+            prev = ty;
+        }
+        Ok(TlvStream {
+            records,
+            schema: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::strict_serialize;
+    use crate::test_helpers::test_encoding_roundtrip;
+
+    #[test]
+    fn test_strict_size() {
+        let vec: Vec<u32> = vec![0, 1, 0xFFFF_FFFF];
+        assert_eq!(vec.strict_size(), 2 + 3 * 4);
+        assert_eq!(vec.strict_size(), strict_serialize(&vec).unwrap().len());
+
+        let nested: Vec<Option<String>> = vec![None, Some(s!("strict"))];
+        assert_eq!(
+            nested.strict_size(),
+            strict_serialize(&nested).unwrap().len()
+        );
+
+        let map: BTreeMap<u8, Vec<u16>> =
+            bmap! { 1 => vec![], 2 => vec![0xFFFF; 4] };
+        assert_eq!(map.strict_size(), strict_serialize(&map).unwrap().len());
+
+        let set: BTreeSet<u64> = bset! { 1, 2, 3 };
+        assert_eq!(set.strict_size(), strict_serialize(&set).unwrap().len());
+    }
+
+    #[test]
+    fn test_control_flow_encoding() {
+        test_encoding_roundtrip(
+            &ControlFlow::<u8, u16>::Continue(0x1234),
+            [0x00, 0x34, 0x12],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &ControlFlow::<u8, u16>::Break(5),
+            [0x01, 0x05],
+        )
+        .unwrap();
+        assert_eq!(
+            ControlFlow::<u8, u16>::strict_deserialize(&[0x02, 0x05]),
+            Err(Error::EnumValueNotKnown("ControlFlow", 2))
+        );
+    }
+
+    /// Test for checking the following rule from LNPBP-5:
+    ///
+    /// `Option<T>` of any type T, which are set to `Option::None` value MUST
+    /// encode as two zero bytes and it MUST be possible to decode optional
+    /// of any type from two zero bytes which MUST result in `Option::None`
+    /// value.
+    #[test]
+    fn test_option_encode_none() {
+        let o1: Option<u8> = None;
+        let o2: Option<u64> = None;
+
+        let two_zero_bytes = &vec![0u8][..];
+
+        assert_eq!(strict_serialize(&o1).unwrap(), two_zero_bytes);
+        assert_eq!(strict_serialize(&o2).unwrap(), two_zero_bytes);
+
+        assert_eq!(Option::<u8>::strict_decode(two_zero_bytes).unwrap(), None);
+        assert_eq!(Option::<u64>::strict_decode(two_zero_bytes).unwrap(), None);
+    }
+
+    /// Test for checking the following rule from LNPBP-5:
+    ///
+    /// `Option<T>` of any type T, which are set to `Option::Some<T>` value MUST
+    /// encode as a `Vec<T>` structure containing a single item equal to the
+    /// `Option::unwrap()` value.
+    #[test]
+    fn test_option_encode_some() {
+        let o1: Option<u8> = Some(0);
+        let o2: Option<u8> = Some(13);
+        let o3: Option<u8> = Some(0xFF);
+        let o4: Option<u64> = Some(13);
+        let o5: Option<u64> = Some(0x1FF);
+        let o6: Option<u64> = Some(0xFFFFFFFFFFFFFFFF);
+        let o7: Option<usize> = Some(13);
+        let o8: Option<usize> = Some(0xFFFFFFFFFFFFFFFF);
+
+        let byte_0 = &[1u8, 0u8][..];
+        let byte_13 = &[1u8, 13u8][..];
+        let byte_255 = &[1u8, 0xFFu8][..];
+        let word_13 = &[1u8, 13u8, 0u8][..];
+        let qword_13 = &[1u8, 13u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8][..];
+        let qword_256 =
+            &[1u8, 0xFFu8, 0x01u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8][..];
+        let qword_max = &[
+            1u8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
+        ][..];
+
+        assert_eq!(strict_serialize(&o1).unwrap(), byte_0);
+        assert_eq!(strict_serialize(&o2).unwrap(), byte_13);
+        assert_eq!(strict_serialize(&o3).unwrap(), byte_255);
+        assert_eq!(strict_serialize(&o4).unwrap(), qword_13);
+        assert_eq!(strict_serialize(&o5).unwrap(), qword_256);
+        assert_eq!(strict_serialize(&o6).unwrap(), qword_max);
+        assert_eq!(strict_serialize(&o7).unwrap(), word_13);
+        assert!(strict_serialize(&o8).err().is_some());
+
+        assert_eq!(Option::<u8>::strict_decode(byte_0).unwrap(), Some(0));
+        assert_eq!(Option::<u8>::strict_decode(byte_13).unwrap(), Some(13));
+        assert_eq!(Option::<u8>::strict_decode(byte_255).unwrap(), Some(0xFF));
+        assert_eq!(Option::<u64>::strict_decode(qword_13).unwrap(), Some(13));
+        assert_eq!(
+            Option::<u64>::strict_decode(qword_256).unwrap(),
+            Some(0x1FF)
+        );
+        assert_eq!(
+            Option::<u64>::strict_decode(qword_max).unwrap(),
+            Some(0xFFFFFFFFFFFFFFFF)
+        );
+        assert_eq!(Option::<usize>::strict_decode(word_13).unwrap(), Some(13));
+        assert_eq!(
+            Option::<usize>::strict_decode(qword_max).unwrap(),
+            Some(0xFFFF)
+        );
+    }
+
+    /// Test trying decoding of non-zero and non-single item vector structures,
+    /// which MUST fail with a specific error.
+    #[test]
+    fn test_option_decode_vec() {
+        assert!(Option::<u8>::strict_decode(&[2u8, 0u8, 0u8, 0u8][..])
+            .err()
+            .is_some());
+        assert!(Option::<u8>::strict_decode(&[3u8, 0u8, 0u8, 0u8][..])
+            .err()
+            .is_some());
+        assert!(Option::<u8>::strict_decode(&[0xFFu8, 0u8, 0u8, 0u8][..])
+            .err()
+            .is_some());
+    }
+
+    #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+    enum RpcError {
+        NotFound,
+        Unauthorized,
+    }
+
+    impl RpcErrorCode for RpcError {
+        fn to_code(&self) -> u16 {
+            match self {
+                RpcError::NotFound => 404,
+                RpcError::Unauthorized => 401,
+            }
+        }
+
+        fn from_code(code: u16) -> Option<Self> {
+            match code {
+                404 => Some(RpcError::NotFound),
+                401 => Some(RpcError::Unauthorized),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_result_encoding() {
+        test_encoding_roundtrip(&Result::<u8, u16>::Ok(5), [0x00, 0x05])
+            .unwrap();
+        test_encoding_roundtrip(
+            &Result::<u8, u16>::Err(404),
+            [0x01, 0x94, 0x01],
+        )
+        .unwrap();
+        assert_eq!(
+            Result::<u8, u16>::strict_deserialize(&[0x02, 0x05]),
+            Err(Error::EnumValueNotKnown("Result", 2))
+        );
+    }
+
+    #[test]
+    fn test_rpc_result_encoding() {
+        test_encoding_roundtrip(
+            &RpcResult::<u8, RpcError>(Ok(5)),
+            [0x00, 0x05],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &RpcResult::<u8, RpcError>(Err(RpcError::NotFound)),
+            [0x01, 0x94, 0x01],
+        )
+        .unwrap();
+        assert_eq!(
+            RpcResult::<u8, RpcError>::strict_deserialize(&[0x01, 0xF4, 0x01]),
+            Err(Error::EnumValueNotKnown(
+                core::any::type_name::<RpcError>(),
+                500
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bound_encoding() {
+        test_encoding_roundtrip(&Bound::<u64>::Unbounded, [0x00]).unwrap();
+        test_encoding_roundtrip(
+            &Bound::Included(0x45a6_u64),
+            [0x01, 0xa6, 0x45, 0, 0, 0, 0, 0, 0],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &Bound::Excluded(0x45a6_u64),
+            [0x02, 0xa6, 0x45, 0, 0, 0, 0, 0, 0],
+        )
+        .unwrap();
+        assert_eq!(
+            Bound::<u64>::strict_deserialize(&[0x03]),
+            Err(Error::EnumValueNotKnown("Bound", 3))
+        );
+        // Unbounded must not consume the value
+        assert_eq!(
+            Bound::<u64>::strict_decode(&[0x00, 0xa6, 0x45][..]),
+            Ok(Bound::Unbounded)
+        );
+    }
+
+    /// Test for checking the following rule from LNPBP-5:
+    ///
+    /// Array of any commitment-serializable type T MUST contain strictly less
+    /// than `0x10000` items and must encode as 16-bit little-endian value
+    /// corresponding to the number of items followed by a direct encoding
+    /// of each of the items.
+    #[test]
+    fn test_vec_encode() {
+        let v1: Vec<u8> = vec![0, 13, 0xFF];
+        let v2: Vec<u8> = vec![13];
+        let v3: Vec<u64> = vec![0, 13, 13, 0x1FF, 0xFFFFFFFFFFFFFFFF];
+        let v4: Vec<u8> =
+            (0..0x1FFFF).map(|item| (item % 0xFF) as u8).collect();
+
+        let s1 = &[3u8, 0u8, 0u8, 13u8, 0xFFu8][..];
+        let s2 = &[1u8, 0u8, 13u8][..];
+        let s3 = &[
+            5u8, 0u8, 0, 0, 0, 0, 0, 0, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0, 13, 0,
+            0, 0, 0, 0, 0, 0, 0xFF, 1, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ][..];
+
+        assert_eq!(strict_serialize(&v1).unwrap(), s1);
+        assert_eq!(strict_serialize(&v2).unwrap(), s2);
+        assert_eq!(strict_serialize(&v3).unwrap(), s3);
+        assert!(strict_serialize(&v4).err().is_some());
+
+        assert_eq!(Vec::<u8>::strict_decode(s1).unwrap(), v1);
+        assert_eq!(Vec::<u8>::strict_decode(s2).unwrap(), v2);
+        assert_eq!(Vec::<u64>::strict_decode(s3).unwrap(), v3);
+    }
+
+    #[test]
+    fn test_map_streaming() {
+        let map: BTreeMap<u16, u64> =
+            (0..1000u16).map(|i| (i, i as u64 * 2)).collect();
+        let data = map.strict_serialize().unwrap();
+
+        let mut sum = 0u64;
+        let mut count = 0usize;
+        strict_decode_map_streaming(&data[..], |_: u16, val: u64| {
+            sum += val;
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 1000);
+        assert_eq!(sum, 999_000);
+
+        let mut count = 0usize;
+        assert_eq!(
+            strict_decode_map_streaming(&data[..], |key: u16, _: u64| {
+                count += 1;
+                if key == 9 {
+                    return Err(Error::DataIntegrityError(s!("stop")));
+                }
+                Ok(())
+            }),
+            Err(Error::DataIntegrityError(s!("stop")))
+        );
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_bit_vec() {
+        let bits = BitVec::from(vec![
+            true, false, false, true, true, false, true, false, // 0x59
+            false, true, true, false, true, // 0x16
+        ]);
+        test_encoding_roundtrip(&bits, [13, 0, 0x59, 0x16]).unwrap();
+        // Padding bits must be ignored
+        assert_eq!(BitVec::strict_deserialize(&[13, 0, 0x59, 0xF6]), Ok(bits));
+        test_encoding_roundtrip(&BitVec::new(), [0, 0]).unwrap();
+    }
+
+    #[test]
+    fn test_delta_map() {
+        let map: DeltaMap<u8> =
+            vec![(1000u64, 0xa1u8), (1001, 0xa2), (1005, 0xa3)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+                .into();
+        test_encoding_roundtrip(
+            &map,
+            [0x03, 0x00, 0xe8, 0x07, 0xa1, 0x01, 0xa2, 0x04, 0xa3],
+        )
+        .unwrap();
+        assert_eq!(
+            DeltaMap::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0x05, 0xa1, 0x00, 0xa2
+            ]),
+            Err(Error::RepeatedValue(s!("5")))
+        );
+        assert!(DeltaMap::<u8>::strict_deserialize(&[
+            0x01, 0x00, 0x85, 0x00, 0xa1
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_prefix_compressed_map() {
+        let map: PrefixCompressedMap<u8> = vec![
+            (s!("ab"), 0xa1u8),
+            (s!("abcd"), 0xa2),
+            (s!("abx"), 0xa3),
+            (s!("b"), 0xa4),
+        ]
+        .into_iter()
+        .collect::<BTreeMap<_, _>>()
+        .into();
+        test_encoding_roundtrip(
+            &map,
+            [
+                0x04, 0x00, // number of items
+                0x00, 0x02, 0x00, b'a', b'b', 0xa1, // "ab"
+                0x02, 0x02, 0x00, b'c', b'd', 0xa2, // "abcd"
+                0x02, 0x01, 0x00, b'x', 0xa3, // "abx"
+                0x00, 0x01, 0x00, b'b', 0xa4, // "b"
+            ],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &PrefixCompressedMap::<u8>::new(),
+            [0x00, 0x00],
+        )
+        .unwrap();
+
+        let map: BTreeMap<String, u8> = (0..20u8)
+            .flat_map(|account| {
+                vec![
+                    (format!("wallet.account.{}.xpub", account), account),
+                    (format!("wallet.account.{}.name", account), account),
+                ]
+            })
+            .collect();
+        let compressed = PrefixCompressedMap::from(map.clone());
+        let encoded = compressed.strict_serialize().unwrap();
+        assert_eq!(
+            PrefixCompressedMap::strict_deserialize(&encoded).unwrap(),
+            compressed
+        );
+        assert!(encoded.len() * 2 < map.strict_serialize().unwrap().len());
+
+        // Shared prefix must be the longest one
+        assert_eq!(
+            PrefixCompressedMap::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0x00, 0x01, 0x00, b'a', 0xa1, 0x00, 0x02, 0x00,
+                b'a', b'b', 0xa2
+            ]),
+            Err(Error::DataIntegrityError(s!(
+                "non-canonical prefix-compressed map encoding"
+            )))
+        );
+        assert_eq!(
+            PrefixCompressedMap::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0x00, 0x01, 0x00, b'b', 0xa1, 0x00, 0x01, 0x00,
+                b'a', 0xa2
+            ]),
+            Err(Error::DataIntegrityError(s!(
+                "non-canonical prefix-compressed map encoding"
+            )))
+        );
+        assert_eq!(
+            PrefixCompressedMap::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0x00, 0x01, 0x00, b'a', 0xa1, 0x01, 0x00, 0x00,
+                0xa2
+            ]),
+            Err(Error::RepeatedValue(s!("a")))
+        );
+        assert_eq!(
+            PrefixCompressedMap::<u8>::strict_deserialize(&[
+                0x01, 0x00, 0x01, 0x01, 0x00, b'a', 0xa1
+            ]),
+            Err(Error::DataIntegrityError(s!(
+                "invalid shared prefix length in prefix-compressed map"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_dict_vec() {
+        test_encoding_roundtrip(
+            &DictVec::from(vec![0xa1u8, 0xa2, 0xa1, 0xa1]),
+            [0x02, 0x00, 0xa1, 0xa2, 0x04, 0x00, 0, 0, 1, 0, 0, 0, 0, 0],
+        )
+        .unwrap();
+        test_encoding_roundtrip(
+            &DictVec::<u8>::new(),
+            [0x00, 0x00, 0x00, 0x00],
+        )
+        .unwrap();
+
+        let values = [
+            s!("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"),
+            s!("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"),
+            s!("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"),
+        ];
+        let vec: Vec<String> =
+            (0..1000).map(|i| values[i * 7 % 3].clone()).collect();
+        let dict_vec = DictVec::from(vec.clone());
+        let encoded = dict_vec.strict_serialize().unwrap();
+        assert_eq!(DictVec::strict_deserialize(&encoded).unwrap(), dict_vec);
+        let plain_len = vec.strict_serialize().unwrap().len();
+        assert_eq!(encoded.len(), 2 + 2 + 42 + 2 + 34 + 2 + 34 + 2 + 2000);
+        assert!(encoded.len() * 10 < plain_len);
+
+        assert_eq!(
+            DictVec::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0xa1, 0xa1, 0x01, 0x00, 0x00, 0x00
+            ]),
+            Err(Error::RepeatedValue(s!("161")))
+        );
+        assert_eq!(
+            DictVec::<u8>::strict_deserialize(&[
+                0x01, 0x00, 0xa1, 0x01, 0x00, 0x01, 0x00
+            ]),
+            Err(Error::ValueOutOfRange("DictVec index", 0..1, 1))
+        );
+        // Dictionary values must be ordered by their first occurrence
+        assert_eq!(
+            DictVec::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0xa1, 0xa2, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00
+            ]),
+            Err(Error::DataIntegrityError(s!(
+                "non-canonical DictVec encoding"
+            )))
+        );
+        // All dictionary values must be used
+        assert_eq!(
+            DictVec::<u8>::strict_deserialize(&[
+                0x02, 0x00, 0xa1, 0xa2, 0x01, 0x00, 0x00, 0x00
+            ]),
+            Err(Error::DataIntegrityError(s!(
+                "non-canonical DictVec encoding"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn test_chunked_vec() {
+        let vec = ChunkedVec::new(vec![1u16, 2, 3, 4, 5], 2);
+        let mut data = vec.strict_serialize().unwrap();
+        assert_eq!(data.len(), 4 + 14 * 2 + 12);
+        assert_eq!(ChunkedVec::strict_deserialize(&data), Ok(vec.clone()));
+        assert_eq!(
+            ChunkedVec::<u16>::strict_decode_lenient(&data[..]),
+            Ok((vec![1, 2, 3, 4, 5], vec![]))
+        );
+
+        // Corrupting first item of the second chunk
+        data[24] ^= 0xFF;
+        assert!(ChunkedVec::<u16>::strict_deserialize(&data).is_err());
+        assert_eq!(
+            ChunkedVec::<u16>::strict_decode_lenient(&data[..]),
+            Ok((vec![1, 2, 5], vec![1]))
+        );
+        data[24] ^= 0xFF;
+
+        // Corrupting length prefix of the second chunk: following chunks
+        // can't be located, so even the lenient decoding fails
+        data[18] ^= 0x01;
+        let err = Error::DataIntegrityError(s!(
+            "ChunkedVec chunk #1 length prefix is corrupted"
+        ));
+        assert_eq!(
+            ChunkedVec::<u16>::strict_deserialize(&data),
+            Err(err.clone())
+        );
+        assert_eq!(
+            ChunkedVec::<u16>::strict_decode_lenient(&data[..]),
+            Err(err)
+        );
+    }
+
+    #[test]
+    fn test_chunked_vec_non_canonical() {
+        fn chunked(chunk_size: u16, chunks: &[&[u16]]) -> Vec<u8> {
+            let mut data = chunk_size.strict_serialize().unwrap();
+            chunks.len().strict_encode(&mut data).unwrap();
+            for chunk in chunks {
+                let payload = chunk.to_vec().strict_serialize().unwrap();
+                let len = payload.len() as u16;
+                len.strict_encode(&mut data).unwrap();
+                (!len).strict_encode(&mut data).unwrap();
+                data.extend(&payload);
+                chunk_crc32(len, &payload).strict_encode(&mut data).unwrap();
+            }
+            data
+        }
+
+        assert_eq!(
+            ChunkedVec::strict_deserialize(&chunked(2, &[&[1, 2], &[3]])),
+            Ok(ChunkedVec::new(vec![1u16, 2, 3], 2))
+        );
+        // Non-final chunk which is not full
+        let data = chunked(2, &[&[1], &[2, 3]]);
+        assert!(ChunkedVec::<u16>::strict_deserialize(&data).is_err());
+        assert_eq!(
+            ChunkedVec::<u16>::strict_decode_lenient(&data[..]),
+            Ok((vec![2, 3], vec![0]))
+        );
+        // Empty chunks
+        assert!(ChunkedVec::<u16>::strict_deserialize(&chunked(
+            2,
+            &[&[1, 2], &[]]
+        ))
+        .is_err());
+        assert!(
+            ChunkedVec::<u16>::strict_deserialize(&chunked(2, &[&[]])).is_err()
+        );
+        // Zero chunk size
+        let data = chunked(0, &[]);
+        assert!(ChunkedVec::<u16>::strict_deserialize(&data).is_err());
+        assert!(ChunkedVec::<u16>::strict_decode_lenient(&data[..]).is_err());
+    }
+
+    // Schema marker intentionally implements no traits except `TlvSchema`
+    struct Schema;
+
+    impl TlvSchema for Schema {
+        const KNOWN_TYPES: &'static [u64] = &[2, 4];
+    }
+
+    #[test]
+    fn test_tlv_stream() {
+        let mut stream = TlvStream::<Schema>::new();
+        stream.insert(4, vec![0xb1, 0xb2]);
+        stream.insert(2, vec![0xa1]);
+        test_encoding_roundtrip(
+            &stream,
+            [0x02, 0x00, 0x02, 0x01, 0xa1, 0x04, 0x02, 0xb1, 0xb2],
+        )
+        .unwrap();
+        assert_eq!(stream.unknown().count(), 0);
+        let mut set = HashSet::new();
+        set.insert(stream.clone());
+        assert!(set.contains(&stream));
+
+        // Unknown odd record is skipped and retained
+        let data = [
+            0x03, 0x00, 0x02, 0x01, 0xa1, 0x03, 0x00, 0x81, 0x01, 0x01, 0xff,
+        ];
+        let stream = TlvStream::<Schema>::strict_deserialize(&data).unwrap();
+        assert_eq!(stream.get(2), Some(&[0xa1][..]));
+        assert_eq!(
+            stream.unknown().collect::<Vec<_>>(),
+            vec![(3, &[][..]), (129, &[0xff][..])]
+        );
+        assert_eq!(stream.strict_serialize().unwrap(), data);
+
+        // Unknown even record
+        assert!(TlvStream::<Schema>::strict_deserialize(&[
+            0x01, 0x00, 0x06, 0x00
+        ])
+        .is_err());
+        // Out-of-order records
+        assert!(TlvStream::<Schema>::strict_deserialize(&[
+            0x02, 0x00, 0x04, 0x00, 0x02, 0x00
+        ])
+        .is_err());
+        // Repeated records
+        assert_eq!(
+            TlvStream::<Schema>::strict_deserialize(&[
+                0x02, 0x00, 0x02, 0x00, 0x02, 0x00
+            ]),
+            Err(Error::RepeatedValue(s!("2")))
+        );
+        // Non-minimal length encoding
+        assert!(TlvStream::<Schema>::strict_deserialize(&[
+            0x01, 0x00, 0x02, 0x81, 0x00, 0xa1
+        ])
+        .is_err());
+        // Truncated value
+        assert_eq!(
+            TlvStream::<Schema>::strict_deserialize(&[
+                0x01, 0x00, 0x02, 0x02, 0xa1
+            ]),
+            Err(Error::UnexpectedEof)
+        );
     }
 }
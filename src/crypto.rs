@@ -22,7 +22,9 @@ use crate::{Error, StrictDecode, StrictEncode};
 #[cfg(feature = "ed25519-dalek")]
 impl StrictEncode for ed25519_dalek::PublicKey {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.as_bytes()[..])?)
+        let data = &self.as_bytes()[..];
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
@@ -39,10 +41,38 @@ impl StrictDecode for ed25519_dalek::PublicKey {
     }
 }
 
+#[cfg(feature = "ed25519-dalek")]
+impl StrictEncode for ed25519_dalek::SecretKey {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let data = &self.as_bytes()[..];
+        e.write_all(data)?;
+        Ok(data.len())
+    }
+}
+
+#[cfg(feature = "ed25519-dalek")]
+impl StrictDecode for ed25519_dalek::SecretKey {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut buf = [0u8; ed25519_dalek::SECRET_KEY_LENGTH];
+        let res = d.read_exact(&mut buf).map_err(Error::from).and_then(|_| {
+            Self::from_bytes(&buf).map_err(|_| {
+                Error::DataIntegrityError(
+                    "invalid Ed25519 secret key data".to_string(),
+                )
+            })
+        });
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut buf);
+        res
+    }
+}
+
 #[cfg(feature = "ed25519-dalek")]
 impl StrictEncode for ed25519_dalek::Signature {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self.as_bytes())?)
+        let data = &self.as_bytes();
+        e.write_all(&data[..])?;
+        Ok(data.len())
     }
 }
 
@@ -59,6 +89,25 @@ impl StrictDecode for ed25519_dalek::Signature {
     }
 }
 
+#[cfg(feature = "x25519-dalek")]
+impl StrictEncode for x25519_dalek::PublicKey {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let data = &self.as_bytes()[..];
+        e.write_all(data)?;
+        Ok(data.len())
+    }
+}
+
+#[cfg(feature = "x25519-dalek")]
+impl StrictDecode for x25519_dalek::PublicKey {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        // Any 32-byte string is a valid Montgomery-u coordinate
+        let mut buf = [0u8; 32];
+        d.read_exact(&mut buf)?;
+        Ok(Self::from(buf))
+    }
+}
+
 #[cfg(feature = "grin_secp256k1zkp")]
 impl StrictEncode for secp256k1zkp::Error {
     #[inline]
@@ -110,7 +159,9 @@ impl StrictDecode for secp256k1zkp::Error {
 impl StrictEncode for secp256k1zkp::pedersen::Commitment {
     #[inline]
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        Ok(e.write(&self[..])?)
+        let data = &self[..];
+        e.write_all(data)?;
+        Ok(data.len())
     }
 }
 
@@ -120,6 +171,14 @@ impl StrictDecode for secp256k1zkp::pedersen::Commitment {
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         let mut buf = [0u8; secp256k1zkp::constants::PEDERSEN_COMMITMENT_SIZE];
         d.read_exact(&mut buf)?;
+        // Serialized pedersen commitments are prefixed with either `0x08` or
+        // `0x09` byte, depending on the parity of the curve point
+        if buf[0] != 0x08 && buf[0] != 0x09 {
+            return Err(Error::DataIntegrityError(format!(
+                "invalid pedersen commitment prefix byte {:#04x}",
+                buf[0]
+            )));
+        }
         Ok(Self::from_vec(buf.to_vec()))
     }
 }
@@ -135,23 +194,18 @@ impl StrictEncode for secp256k1zkp::pedersen::RangeProof {
 #[cfg(feature = "grin_secp256k1zkp")]
 impl StrictDecode for secp256k1zkp::pedersen::RangeProof {
     #[inline]
-    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
         use secp256k1zkp::constants::MAX_PROOF_SIZE;
-        let data = Vec::<u8>::strict_decode(d)?;
-        match data.len() {
-            len if len < MAX_PROOF_SIZE => {
-                let mut buf = [0; MAX_PROOF_SIZE];
-                buf[..len].copy_from_slice(&data);
-                Ok(Self {
-                    proof: buf,
-                    plen: len,
-                })
-            }
-            invalid_len => Err(Error::DataIntegrityError(format!(
-                "Wrong bulletproof data size: expected no more than {}, got {}",
-                MAX_PROOF_SIZE, invalid_len
-            ))),
+        let len = usize::strict_decode(&mut d)?;
+        if len > MAX_PROOF_SIZE {
+            return Err(Error::ExceedMaxItems(len));
         }
+        let mut buf = [0; MAX_PROOF_SIZE];
+        d.read_exact(&mut buf[..len])?;
+        Ok(Self {
+            proof: buf,
+            plen: len,
+        })
     }
 }
 
@@ -174,8 +228,33 @@ mod test {
 
     #[test]
     #[cfg(feature = "ed25519-dalek")]
-    fn x25519() {
-        use ed25519_dalek::Signer;
+    fn ed25519_secret_key() {
+        let keypair = ed25519_dalek::Keypair::generate(&mut rand::thread_rng());
+
+        let ser = keypair.secret.strict_serialize().unwrap();
+        assert_eq!(ser.len(), 32);
+        assert_eq!(
+            ed25519_dalek::SecretKey::strict_deserialize(&ser)
+                .unwrap()
+                .as_bytes(),
+            keypair.secret.as_bytes()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519-dalek")]
+    fn ed25519_secret_key_garbage() {
+        assert_eq!(
+            ed25519_dalek::SecretKey::strict_deserialize(&[0x5au8; 31][..])
+                .unwrap_err(),
+            Error::UnexpectedEof
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519-dalek")]
+    fn ed25519_signature() {
+        use ed25519_dalek::{Signer, Verifier};
 
         let keypair = ed25519_dalek::Keypair::generate(&mut rand::thread_rng());
         let message: &[u8] = b"This is a test of the tsunami alert system.";
@@ -183,9 +262,40 @@ mod test {
 
         let ser = signature.strict_serialize().unwrap();
         assert_eq!(ser.len(), 64);
+        let decoded =
+            ed25519_dalek::Signature::strict_deserialize(ser).unwrap();
+        assert_eq!(decoded, signature);
+        assert!(keypair.public.verify(message, &decoded).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "x25519-dalek")]
+    fn x25519() {
+        // Alice's public key from RFC 7748 test vectors
+        static PK_BYTES: [u8; 32] = [
+            0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d,
+            0xdc, 0xb4, 0x3e, 0xf7, 0x5a, 0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38,
+            0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+        ];
+        let pk = x25519_dalek::PublicKey::from(PK_BYTES);
+
+        let ser = pk.strict_serialize().unwrap();
+        assert_eq!(ser, PK_BYTES);
+        assert_eq!(
+            x25519_dalek::PublicKey::strict_deserialize(ser)
+                .unwrap()
+                .as_bytes(),
+            &PK_BYTES
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "x25519-dalek")]
+    fn x25519_garbage() {
         assert_eq!(
-            ed25519_dalek::Signature::strict_deserialize(ser).unwrap(),
-            signature
+            x25519_dalek::PublicKey::strict_deserialize(&[0x85u8; 31][..])
+                .unwrap_err(),
+            Error::UnexpectedEof
         );
     }
 
@@ -213,6 +323,32 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(feature = "grin_secp256k1zkp")]
+    fn pedersen_value_commitment() {
+        let secp = secp256k1zkp::Secp256k1::with_caps(
+            secp256k1zkp::ContextFlag::Commit,
+        );
+        let blind =
+            secp256k1zkp::SecretKey::from_slice(&secp, &[0x5a; 32]).unwrap();
+        let commitment = secp.commit(0x79833565, blind).unwrap();
+
+        let ser = commitment.strict_serialize().unwrap();
+        assert_eq!(ser.len(), 33);
+        assert_eq!(
+            secp256k1zkp::pedersen::Commitment::strict_deserialize(&ser)
+                .unwrap(),
+            commitment
+        );
+
+        let mut invalid = ser;
+        invalid[0] = 0x02;
+        assert!(matches!(
+            secp256k1zkp::pedersen::Commitment::strict_deserialize(invalid),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+
     #[test]
     #[cfg(feature = "grin_secp256k1zkp")]
     fn bulletproof() {
@@ -239,6 +375,20 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(feature = "grin_secp256k1zkp")]
+    fn bulletproof_size_limit() {
+        use secp256k1zkp::constants::MAX_PROOF_SIZE;
+
+        let mut data = (MAX_PROOF_SIZE as u16 + 1).to_le_bytes().to_vec();
+        data.extend(vec![0u8; MAX_PROOF_SIZE + 1]);
+        assert_eq!(
+            secp256k1zkp::pedersen::RangeProof::strict_deserialize(data)
+                .unwrap_err(),
+            Error::ExceedMaxItems(MAX_PROOF_SIZE + 1)
+        );
+    }
+
     /* TODO: #25 Uncomment this test once `grin_secp256k1zkp::Error` impl `Ord`
     #[test]
     #[cfg(feature = "grin_secp256k1zkp")]
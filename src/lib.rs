@@ -32,8 +32,9 @@
 //!
 //! Library defines two main traits, [`StrictEncode`] and [`StrictDecode`],
 //! which should be implemented on each type that requires to be represented
-//! for client-side-validation. It also defines possible encoding error cases
-//! with [`Error`] and provides derivation macros
+//! for client-side-validation, plus auxiliary [`StrictSize`] trait for
+//! computing the size of the encoded data. It also defines possible encoding
+//! error cases with [`Error`] and provides derivation macros
 //! `#[derive(StrictEncode, StrictDecode)]`, which are a part of
 //! `strict_encode_derive` sub-crate and represented by a default feature
 //! `derive`. Finally, it implements strict encoding traits for main data types
@@ -43,10 +44,17 @@
 //! - `chrono` (used by default): date & time types from `chrono` crate
 //! - `miniscript`: types defined in bitcoin Miniscript
 //! - `crypto`: non-bitcoin cryptographic primitives, which include Ed25519
-//!   curve, X25519 signatures from `ed25519-dalek` library and pedersen
-//!   commitments + bulletproofs from `grin_secp256k1zkp` library. Encodings for
+//!   curve and signatures from `ed25519-dalek` library, X25519 public keys
+//!   from `x25519-dalek` library and pedersen commitments + bulletproofs
+//!   from `grin_secp256k1zkp` library. Encodings for
 //!   other cryptography-related types, such as Secp256k1 and hashes, are always
 //!   included as a part of the library - see NB below.
+//! - `zeroize`: zeroization of the intermediary buffers used during decoding
+//!   of secret keys
+//! - `tokio`: asynchronous versions of strict encoding traits operating on
+//!   `tokio` readers and writers
+//! - `serde`: helpers for embedding strict-encoded data into serde-serialized
+//!   documents (see [`serde_helpers`] module)
 //!
 //! NB: this crate requires `bitcoin` as an upstream dependency since many of
 //!     strict-encoded formats are standardized as using *bitcoin consensus
@@ -55,7 +63,7 @@
 #[cfg(feature = "derive")]
 pub extern crate strict_encoding_derive as derive;
 #[cfg(feature = "derive")]
-pub use derive::{StrictDecode, StrictEncode};
+pub use derive::{StrictDecode, StrictEncode, StrictSize};
 
 #[macro_use]
 extern crate amplify;
@@ -77,10 +85,34 @@ mod miniscript;
 pub mod net;
 mod pointers;
 mod primitives;
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
 pub mod strategies;
+#[cfg(feature = "tokio")]
+mod tokio;
 
+pub use self::bitcoin_hashes::{
+    strict_deserialize_checked, strict_merkle_root, strict_serialize_checked,
+    ArchiveWriter,
+};
+#[cfg(feature = "tokio")]
+pub use self::tokio::{AsyncStrictDecode, AsyncStrictEncode, StrictFuture};
+pub use collections::{
+    strict_decode_map_streaming, BitVec, ChunkedVec, DeltaMap, DictVec,
+    PrefixCompressedMap, RpcErrorCode, RpcResult, TlvSchema, TlvStream,
+};
 pub use strategies::Strategy;
 
+#[cfg(feature = "bitcoin")]
+pub use self::bitcoin::{
+    strict_decode_amounts_sum, strict_decode_network_or_magic,
+    strict_decode_psbt_canonical, strict_decode_script_consensus_limited,
+    strict_decode_tx_vec_bounded, strict_decode_witness_bounded,
+    strict_encode_taproot_output, AnnotatedTx, CompactChildNumber,
+    CompactDerivationPath, DerSignature, ProprietaryField, ScriptDictVec,
+    ValueSortedTxOuts, MAX_SCRIPT_ELEMENT_SIZE, MAX_SCRIPT_SIZE,
+};
+
 /// Re-exporting extended read and write functions from bitcoin consensus
 /// module so others may use semantic convenience
 /// `strict_encode::ReadExt`
@@ -91,6 +123,7 @@ use amplify::IoError;
 use std::fmt;
 use std::io;
 use std::ops::Range;
+use std::path::Path;
 use std::string::FromUtf8Error;
 
 /// Binary encoding according to the strict rules that usually apply to
@@ -107,7 +140,10 @@ pub trait StrictEncode {
     /// error type.
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error>;
 
-    /// Serializes data as a byte array using [`strict_encode()`] function
+    /// Serializes data as a byte array using [`strict_encode()`] function.
+    /// Types implementing [`StrictSize`] may use
+    /// [`StrictSize::strict_serialize_sized`] instead, which allocates the
+    /// whole buffer upfront.
     fn strict_serialize(&self) -> Result<Vec<u8>, Error> {
         let mut e = vec![];
         let _ = self.strict_encode(&mut e)?;
@@ -135,6 +171,63 @@ pub trait StrictDecode: Sized {
     }
 }
 
+/// Size of the data in strict encoding, which allows to allocate buffers and
+/// network frames before the actual encoding takes place.
+///
+/// Types with a known size (like primitive integers) and types which derive
+/// the trait with `#[derive(StrictSize)]` compute the size without encoding
+/// the data. Other types may rely on the default implementation, which
+/// encodes the data into a writer counting and discarding the bytes.
+///
+/// Types which all values are encoded into the same number of bytes report
+/// this number as [`StrictSize::LEN`], which can be used in constant
+/// expressions, like array lengths. Derived implementations provide it for
+/// structures which all fields have fixed size, and for enums which all
+/// variants have the same fixed size.
+pub trait StrictSize: StrictEncode {
+    /// Number of bytes produced by strict encoding of any value of the type,
+    /// or `None` if the size depends on the value.
+    const LEN: Option<usize> = None;
+
+    /// Returns number of bytes which will be produced by strict encoding of
+    /// the value. If the value can't be strict-encoded (for instance, it
+    /// contains collection with more than [`u16::MAX`] items) the result is
+    /// unspecified.
+    fn strict_size(&self) -> usize {
+        if let Some(len) = Self::LEN {
+            return len;
+        }
+        let mut sink = CountingSink(0);
+        let _ = self.strict_encode(&mut sink);
+        sink.0
+    }
+
+    /// Serializes data as a byte array using [`StrictEncode::strict_encode`]
+    /// into a buffer pre-allocated with the [`StrictSize::strict_size`] of
+    /// the value, so no reallocations happen during the encoding.
+    fn strict_serialize_sized(&self) -> Result<Vec<u8>, Error> {
+        let mut e = Vec::with_capacity(self.strict_size());
+        let _ = self.strict_encode(&mut e)?;
+        Ok(e)
+    }
+}
+
+/// Writer discarding all data and counting the number of bytes written
+struct CountingSink(usize);
+
+impl io::Write for CountingSink {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Convenience method for strict encoding of data structures implementing
 /// [StrictEncode] into a byte vector.
 pub fn strict_serialize<T>(data: &T) -> Result<Vec<u8>, Error>
@@ -146,6 +239,16 @@ where
     Ok(encoder.into_inner())
 }
 
+/// Convenience method for strict encoding of data structures implementing
+/// [StrictSize] into a byte vector, which is allocated only once with the
+/// exact size of the encoded data.
+pub fn strict_serialize_sized<T>(data: &T) -> Result<Vec<u8>, Error>
+where
+    T: StrictSize,
+{
+    data.strict_serialize_sized()
+}
+
 /// Convenience method for strict decoding of data structures implementing
 /// [StrictDecode] from any byt data source.
 pub fn strict_deserialize<T>(data: impl AsRef<[u8]>) -> Result<T, Error>
@@ -164,15 +267,298 @@ where
     }
 }
 
+/// Convenience method for strict encoding of data structures implementing
+/// [StrictEncode] into a lowercase hex string.
+pub fn strict_serialize_hex<T>(data: &T) -> Result<String, Error>
+where
+    T: StrictEncode,
+{
+    use ::bitcoin_hashes::hex::ToHex;
+    Ok(strict_serialize(data)?.to_hex())
+}
+
+/// Convenience method for strict decoding of data structures implementing
+/// [StrictDecode] from a hex string. Invalid hex strings result in
+/// [`Error::DataIntegrityError`].
+pub fn strict_deserialize_hex<T>(hex: &str) -> Result<T, Error>
+where
+    T: StrictDecode,
+{
+    use ::bitcoin_hashes::hex::FromHex;
+    let data = Vec::<u8>::from_hex(hex).map_err(|err| {
+        Error::DataIntegrityError(format!("invalid hex string: {}", err))
+    })?;
+    strict_deserialize(data)
+}
+
+/// Writes strict encoding of the value as a frame prefixed with its length,
+/// represented as a little-endian `u32` value. Returns the number of bytes
+/// written, including the length prefix.
+pub fn strict_encode_framed<T, W>(
+    value: &T,
+    mut writer: W,
+) -> Result<usize, Error>
+where
+    T: StrictEncode,
+    W: io::Write,
+{
+    let data = strict_serialize(value)?;
+    let len = data.len();
+    if len > u32::MAX as usize {
+        return Err(Error::ExceedMaxItems(len));
+    }
+    (len as u32).strict_encode(&mut writer)?;
+    writer.write_all(&data)?;
+    Ok(len + 4)
+}
+
+/// Reads a frame written by [`strict_encode_framed`] and decodes the value
+/// from it. Errors with [`Error::DataNotEntirelyConsumed`] if the value does
+/// not take the whole frame.
+pub fn strict_decode_framed<T, R>(mut reader: R) -> Result<T, Error>
+where
+    T: StrictDecode,
+    R: io::Read,
+{
+    let len = u32::strict_decode(&mut reader)? as usize;
+    // The buffer grows with the data actually read, so a malformed length
+    // prefix does not lead to a huge memory allocation
+    let mut data = Vec::new();
+    io::Read::read_to_end(&mut reader.take(len as u64), &mut data)?;
+    if data.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    strict_deserialize(data)
+}
+
+/// Strict-encodes the value into the file at the given path, creating the
+/// file or truncating the existing one. Returns the number of bytes written.
+pub fn strict_encode_file<T, P>(value: &T, path: P) -> Result<usize, Error>
+where
+    T: StrictEncode,
+    P: AsRef<Path>,
+{
+    let file = std::fs::File::create(path)?;
+    let mut writer = io::BufWriter::new(file);
+    let len = value.strict_encode(&mut writer)?;
+    // Flushing explicitly, since dropping the writer ignores errors
+    io::Write::flush(&mut writer)?;
+    Ok(len)
+}
+
+/// Strict-decodes the value from the file at the given path. Like
+/// [`strict_deserialize`], fails with [`Error::DataNotEntirelyConsumed`] if
+/// the file contains extra data after the value.
+pub fn strict_decode_file<T, P>(path: P) -> Result<T, Error>
+where
+    T: StrictDecode,
+    P: AsRef<Path>,
+{
+    let file = std::fs::File::open(path)?;
+    let mut reader = io::BufReader::new(file);
+    let rv = T::strict_decode(&mut reader)?;
+
+    // Fail if data are not consumed entirely.
+    if io::Read::read(&mut reader, &mut [0u8])? == 0 {
+        Ok(rv)
+    } else {
+        Err(Error::DataNotEntirelyConsumed)
+    }
+}
+
+/// Reader wrapper counting the number of bytes consumed from the inner reader.
+/// Allows to locate the position in the data stream at which the decoding has
+/// failed.
+#[derive(Clone, Debug)]
+pub struct StrictReader<R: io::Read> {
+    inner: R,
+    position: usize,
+    recorded: Option<Vec<u8>>,
+}
+
+impl<R: io::Read> StrictReader<R> {
+    /// Constructs reader wrapper with zero initial position
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        StrictReader {
+            inner,
+            position: 0,
+            recorded: None,
+        }
+    }
+
+    /// Constructs reader wrapper which, in addition to counting, keeps copy
+    /// of all data consumed from the inner reader
+    #[inline]
+    pub fn with_recording(inner: R) -> Self {
+        StrictReader {
+            inner,
+            position: 0,
+            recorded: Some(vec![]),
+        }
+    }
+
+    /// Returns number of bytes consumed from the inner reader
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns data consumed from the inner reader, if the reader was
+    /// constructed with [`StrictReader::with_recording`]
+    #[inline]
+    pub fn recorded(&self) -> Option<&[u8]> {
+        self.recorded.as_deref()
+    }
+
+    /// Releases the inner reader
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decodes value of type `T` from the reader. On failure, attaches to the
+    /// error the reader position at which the failure has happened.
+    #[inline]
+    pub fn decode<T: StrictDecode>(&mut self) -> Result<T, PositionedError> {
+        T::strict_decode(&mut *self).map_err(|error| PositionedError {
+            offset: self.position,
+            error,
+        })
+    }
+}
+
+impl<R: io::Read> io::Read for StrictReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.position += len;
+        if let Some(recorded) = &mut self.recorded {
+            recorded.extend_from_slice(&buf[..len]);
+        }
+        Ok(len)
+    }
+}
+
+/// Writer wrapper counting the number of bytes actually written to the inner
+/// writer. Allows to verify that strict encoders report the correct length of
+/// the encoded data.
+#[derive(Clone, Debug)]
+pub struct StrictWriter<W: io::Write> {
+    inner: W,
+    position: usize,
+}
+
+impl<W: io::Write> StrictWriter<W> {
+    /// Constructs writer wrapper with zero initial position
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        StrictWriter { inner, position: 0 }
+    }
+
+    /// Returns number of bytes written to the inner writer
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Releases the inner writer
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Encodes value into the writer, returning the number of bytes written.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if the length reported by the encoder does not match
+    /// the number of bytes it has actually written.
+    pub fn encode<T: StrictEncode>(
+        &mut self,
+        value: &T,
+    ) -> Result<usize, Error> {
+        let start = self.position;
+        let len = value.strict_encode(&mut *self)?;
+        debug_assert_eq!(
+            len,
+            self.position - start,
+            "strict encoder for `{}` reported wrong length of the encoded data",
+            std::any::type_name::<T>()
+        );
+        Ok(len)
+    }
+}
+
+impl<W: io::Write> io::Write for StrictWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.position += len;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decoding error together with the position in the data stream at which it
+/// has happened. The position is displayed as a prefix of the error message,
+/// like `at offset 42: <error description>`.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub struct PositionedError {
+    /// Number of bytes consumed from the data stream at the moment of the
+    /// failure. Since the failure is detected only after the data are read,
+    /// the offset points right after the malformed value (for instance, after
+    /// the unknown enum tag or the out-of-range integer).
+    pub offset: usize,
+
+    /// Decoding error
+    pub error: Error,
+}
+
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.error)
+    }
+}
+
+/// Variant of [`strict_deserialize`] reporting the position in the data at
+/// which the decoding has failed
+pub fn strict_deserialize_positioned<T>(
+    data: impl AsRef<[u8]>,
+) -> Result<T, PositionedError>
+where
+    T: StrictDecode,
+{
+    let data = data.as_ref();
+    let mut reader = StrictReader::new(data);
+    let rv = reader.decode()?;
+
+    // Fail if data are not consumed entirely.
+    if reader.position() == data.len() {
+        Ok(rv)
+    } else {
+        Err(PositionedError {
+            offset: reader.position(),
+            error: Error::DataNotEntirelyConsumed,
+        })
+    }
+}
+
 /// Possible errors during strict encoding and decoding process
 #[derive(Clone, PartialEq, Eq, Debug, Display, From, Error)]
 #[display(doc_comments)]
 pub enum Error {
     /// I/O error during data strict encoding
-    #[from(io::Error)]
-    #[from(io::ErrorKind)]
     Io(IoError),
 
+    /// Data are truncated: the end of the data stream was reached before the
+    /// decoding was complete
+    UnexpectedEof,
+
     /// String data are not in valid UTF-8 encoding
     #[from]
     Utf8Conversion(std::str::Utf8Error),
@@ -231,8 +617,312 @@ impl From<Error> for fmt::Error {
     }
 }
 
+impl From<io::ErrorKind> for Error {
+    /// Distinguishes [`io::ErrorKind::UnexpectedEof`] as
+    /// [`Error::UnexpectedEof`], keeping all other I/O errors as [`Error::Io`]
+    fn from(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::UnexpectedEof => Error::UnexpectedEof,
+            kind => Error::Io(kind.into()),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(err: io::Error) -> Self {
+        err.kind().into()
+    }
+}
+
+impl From<Error> for io::Error {
+    /// I/O errors are converted back into the original error kind; all other
+    /// errors are represented as [`io::ErrorKind::InvalidData`]
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err.into(),
+            Error::UnexpectedEof => io::ErrorKind::UnexpectedEof.into(),
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
 impl From<FromUtf8Error> for Error {
     fn from(err: FromUtf8Error) -> Self {
         Error::Utf8Conversion(err.utf8_error())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reader_position() {
+        let data: Vec<(u16, u32)> = vec![(1, 2), (3, 4)];
+        let ser = strict_serialize(&data).unwrap();
+        assert_eq!(ser.len(), 14);
+
+        let mut reader = StrictReader::new(&ser[..]);
+        assert_eq!(reader.decode::<u16>().unwrap(), 2);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.decode::<(u16, u32)>().unwrap(), (1, 2));
+        assert_eq!(reader.position(), 8);
+        assert_eq!(reader.recorded(), None);
+
+        let mut reader = StrictReader::with_recording(&ser[..]);
+        assert_eq!(reader.decode::<Vec<(u16, u32)>>().unwrap(), data);
+        assert_eq!(reader.recorded(), Some(&ser[..]));
+
+        assert_eq!(
+            strict_deserialize_positioned::<Vec<(u16, u32)>>(&ser).unwrap(),
+            data
+        );
+    }
+
+    #[derive(
+        Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode, StrictSize,
+    )]
+    #[strict_encoding(crate = crate)]
+    struct Composite {
+        name: String,
+        items: Vec<(u16, Option<u64>)>,
+        flag: bool,
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate, by_value, repr = u8)]
+    #[repr(u8)]
+    enum Kind {
+        Plain = 1,
+        Tagged = 2,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct Header {
+        version: u16,
+        items: Vec<u32>,
+        kind: Kind,
+    }
+
+    #[test]
+    fn test_positioned_display() {
+        let header = Header {
+            version: 1,
+            items: vec![7, 8],
+            kind: Kind::Tagged,
+        };
+        let mut data = strict_serialize(&header).unwrap();
+        assert_eq!(data.len(), 2 + 2 + 8 + 1);
+        assert_eq!(strict_deserialize_positioned(&data), Ok(header));
+
+        // Unknown enum tag at offset 12
+        data[12] = 3;
+        let err = strict_deserialize_positioned::<Header>(&data).unwrap_err();
+        assert_eq!(err.offset, 13);
+        assert_eq!(err.error, Error::EnumValueNotKnown("Kind", 3));
+        assert_eq!(
+            err.to_string(),
+            format!("at offset 13: {}", Error::EnumValueNotKnown("Kind", 3))
+        );
+
+        // Data truncated right after the vector length
+        let err =
+            strict_deserialize_positioned::<Header>(&data[..4]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("at offset 4: {}", Error::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_serialize_sized() {
+        let vec = (0..8000u64).collect::<Vec<_>>();
+        let data = strict_serialize_sized(&vec).unwrap();
+        assert_eq!(data.len(), 2 + 8 * 8000);
+        // Buffer was allocated once with the exact size and never regrown
+        assert_eq!(data.capacity(), data.len());
+        assert_eq!(data, strict_serialize(&vec).unwrap());
+        assert_eq!(strict_deserialize::<Vec<u64>>(&data), Ok(vec));
+
+        let composite = Composite {
+            name: s!("sized"),
+            items: vec![(1, None), (2, Some(3))],
+            flag: true,
+        };
+        let data = composite.strict_serialize_sized().unwrap();
+        assert_eq!(data.capacity(), data.len());
+        assert_eq!(data, composite.strict_serialize().unwrap());
+    }
+
+    #[test]
+    fn test_file() {
+        let path = std::env::temp_dir()
+            .join(format!("strict_encoding_test_{}.bin", std::process::id()));
+        let composite = Composite {
+            name: s!("file"),
+            items: vec![(1, Some(2)), (3, None)],
+            flag: false,
+        };
+
+        let len = strict_encode_file(&composite, &path).unwrap();
+        assert_eq!(len, composite.strict_serialize().unwrap().len());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len as u64);
+        assert_eq!(strict_decode_file::<Composite, _>(&path), Ok(composite));
+
+        // Extra data after the value
+        strict_encode_file(&s!("extra"), &path).unwrap();
+        assert_eq!(
+            strict_decode_file::<u8, _>(&path),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+        assert_eq!(
+            strict_decode_file::<u64, _>(&path),
+            Err(Error::UnexpectedEof)
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            strict_decode_file::<u8, _>(&path),
+            Err(Error::Io(io::ErrorKind::NotFound.into()))
+        );
+    }
+
+    #[test]
+    fn test_writer_position() {
+        let composite = Composite {
+            name: s!("composite"),
+            items: vec![(1, None), (2, Some(3))],
+            flag: true,
+        };
+
+        let mut writer = StrictWriter::new(vec![]);
+        let len = writer.encode(&composite).unwrap();
+        assert_eq!(len, writer.position());
+        assert_eq!(len, 2 + 9 + 2 + 3 + 11 + 1);
+        assert_eq!(writer.encode(&0u32).unwrap(), 4);
+        assert_eq!(writer.position(), len + 4);
+
+        let data = writer.into_inner();
+        assert_eq!(data.len(), len + 4);
+        assert_eq!(Composite::strict_deserialize(&data[..len]), Ok(composite));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(strict_serialize_hex(&0xCAFE_u16).unwrap(), "feca");
+        assert_eq!(strict_deserialize_hex::<u16>("feca").unwrap(), 0xCAFE);
+        assert_eq!(strict_deserialize_hex::<u16>("FECA").unwrap(), 0xCAFE);
+
+        let composite = Composite {
+            name: s!("hex"),
+            items: vec![(1, Some(2))],
+            flag: false,
+        };
+        let hex = strict_serialize_hex(&composite).unwrap();
+        assert_eq!(hex, "03006865780100010001020000000000000000");
+        assert_eq!(
+            strict_deserialize_hex::<Composite>(&hex).unwrap(),
+            composite
+        );
+
+        assert!(matches!(
+            strict_deserialize_hex::<u16>("fec"),
+            Err(Error::DataIntegrityError(_))
+        ));
+        assert!(matches!(
+            strict_deserialize_hex::<u16>("zzzz"),
+            Err(Error::DataIntegrityError(_))
+        ));
+        assert_eq!(
+            strict_deserialize_hex::<u16>("feca00"),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+    }
+
+    #[test]
+    fn test_unexpected_eof() {
+        assert_eq!(
+            strict_deserialize::<u32>(&[0x01, 0x02, 0x03]),
+            Err(Error::UnexpectedEof)
+        );
+        assert_eq!(
+            strict_deserialize::<String>(&[0x05, 0x00, b'a']),
+            Err(Error::UnexpectedEof)
+        );
+        assert_eq!(
+            Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Error::UnexpectedEof
+        );
+        assert_eq!(
+            Error::from(io::ErrorKind::PermissionDenied),
+            Error::Io(io::ErrorKind::PermissionDenied.into())
+        );
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let err = Error::DataIntegrityError(s!("broken data"));
+        let msg = err.to_string();
+        let io_err = io::Error::from(err.clone());
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(io_err.to_string(), msg);
+        assert_eq!(
+            io_err.into_inner().unwrap().downcast_ref::<Error>(),
+            Some(&err)
+        );
+
+        let io_err = io::Error::from(Error::from(io::ErrorKind::UnexpectedEof));
+        assert_eq!(io_err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_framed() {
+        let mut buf = vec![];
+        assert_eq!(strict_encode_framed(&s!("first"), &mut buf).unwrap(), 11);
+        assert_eq!(strict_encode_framed(&0xCAFE_u16, &mut buf).unwrap(), 6);
+        assert_eq!(buf[..4], [7, 0, 0, 0]);
+        assert_eq!(buf[11..15], [2, 0, 0, 0]);
+
+        let mut reader = &buf[..];
+        assert_eq!(
+            strict_decode_framed::<String, _>(&mut reader).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            strict_decode_framed::<u16, _>(&mut reader).unwrap(),
+            0xCAFE
+        );
+        assert!(reader.is_empty());
+
+        // Frame is larger than the value
+        assert_eq!(
+            strict_decode_framed::<u16, _>(&[3u8, 0, 0, 0, 1, 2, 3][..]),
+            Err(Error::DataNotEntirelyConsumed)
+        );
+        // Frame is truncated
+        assert_eq!(
+            strict_decode_framed::<u16, _>(&[2u8, 0, 0, 0, 1][..]),
+            Err(Error::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_positioned_error() {
+        let ser = strict_serialize(&vec![(1u16, 2u32), (3, 4)]).unwrap();
+
+        // EOF inside the `u32` field of the second item
+        let err = strict_deserialize_positioned::<Vec<(u16, u32)>>(&ser[..10])
+            .unwrap_err();
+        assert_eq!(err.offset, 10);
+        assert_eq!(err.error, Error::UnexpectedEof);
+
+        let mut extended = ser.clone();
+        extended.push(0);
+        let err = strict_deserialize_positioned::<Vec<(u16, u32)>>(&extended)
+            .unwrap_err();
+        assert_eq!(err.offset, 14);
+        assert_eq!(err.error, Error::DataNotEntirelyConsumed);
+    }
+}
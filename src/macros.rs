@@ -57,3 +57,77 @@ macro_rules! strict_decode_self {
         }
     };
 }
+
+/// Macro implementing [`StrictEncode`](crate::StrictEncode) and
+/// [`StrictDecode`](crate::StrictDecode) for a newtype by delegating to its
+/// single inner field, which is the first tuple field by default. Other field
+/// may be specified by its tuple index or name as the last argument.
+///
+/// ```
+/// # #[macro_use] extern crate strict_encoding;
+/// struct Height(u32);
+/// impl_strict_newtype!(Height, u32);
+///
+/// struct Offset {
+///     bytes: u64,
+/// }
+/// impl_strict_newtype!(Offset, u64, bytes);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! impl_strict_newtype {
+    ( $type:ty, $inner:ty ) => {
+        $crate::impl_strict_newtype!($type, $inner, 0);
+    };
+
+    ( $type:ty, $inner:ty, $field:tt ) => {
+        impl $crate::StrictEncode for $type {
+            #[inline]
+            fn strict_encode<E: ::std::io::Write>(
+                &self,
+                e: E,
+            ) -> Result<usize, $crate::Error> {
+                $crate::StrictEncode::strict_encode(&self.$field, e)
+            }
+        }
+
+        impl $crate::StrictDecode for $type {
+            #[inline]
+            fn strict_decode<D: ::std::io::Read>(
+                d: D,
+            ) -> Result<Self, $crate::Error> {
+                Ok(Self {
+                    $field: <$inner as $crate::StrictDecode>::strict_decode(d)?,
+                })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{StrictDecode, StrictEncode};
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    struct Height(u32);
+    impl_strict_newtype!(Height, u32);
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Label {
+        name: String,
+    }
+    impl_strict_newtype!(Label, String, name);
+
+    #[test]
+    fn test_newtype() {
+        let height = Height(0x0102_0304);
+        let data = height.strict_serialize().unwrap();
+        assert_eq!(data, vec![0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(Height::strict_deserialize(data).unwrap(), height);
+
+        let label = Label { name: s!("label") };
+        let data = label.strict_serialize().unwrap();
+        assert_eq!(data, s!("label").strict_serialize().unwrap());
+        assert_eq!(Label::strict_deserialize(data).unwrap(), label);
+    }
+}
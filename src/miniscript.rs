@@ -714,8 +714,10 @@ where
 #[cfg(test)]
 mod test {
     use crate::test_helpers::*;
-    // use crate::StrictEncode;
-    use miniscript::{policy, Descriptor, Miniscript, Segwitv0};
+    use crate::{Error, StrictDecode, StrictEncode};
+    use miniscript::{
+        policy, Descriptor, DescriptorPublicKey, Miniscript, Segwitv0,
+    };
     use std::str::FromStr;
 
     #[test]
@@ -810,4 +812,71 @@ mod test {
             test_object_encoding_roundtrip(&descr).unwrap();
         }
     }
+
+    #[test]
+    fn test_descriptor_single_keys() {
+        let descr = Descriptor::<bitcoin::PublicKey>::from_str(
+            "wsh(multi(2,\
+            03789ed0bb717d88f7d321a368d905e7430207ebbd82bd342cf11ae157a7ace5fd,\
+            03dbc6764b8884a92e871274b87583e6d5c2a58819473e17e107ef3f6aa5a61626))",
+        )
+        .unwrap();
+        test_object_encoding_roundtrip(&descr).unwrap();
+
+        let mut data = descr.strict_serialize().unwrap();
+        data[0] = 0x2f;
+        assert!(matches!(
+            Descriptor::<bitcoin::PublicKey>::strict_deserialize(data),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+
+    #[test]
+    fn test_descriptor_public_key() {
+        const SET: [&str; 4] = [
+            "03789ed0bb717d88f7d321a368d905e7430207ebbd82bd342cf11ae157a7ace5fd",
+            "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB/1/0/*",
+            "[d34db33f/44'/0'/0']xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/1/*",
+            "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB/0'/*'",
+        ];
+
+        for s in &SET {
+            let key = DescriptorPublicKey::from_str(s).unwrap();
+            test_object_encoding_roundtrip(&key).unwrap();
+            assert_eq!(
+                DescriptorPublicKey::strict_deserialize(
+                    key.strict_serialize().unwrap()
+                )
+                .unwrap()
+                .to_string(),
+                *s
+            );
+        }
+
+        // Unknown wildcard type
+        let key = DescriptorPublicKey::from_str(SET[1]).unwrap();
+        let mut data = key.strict_serialize().unwrap();
+        *data.last_mut().unwrap() = 0x03;
+        assert!(matches!(
+            DescriptorPublicKey::strict_deserialize(data),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+
+    #[test]
+    fn test_miniscript_fragment() {
+        let ms =
+            Miniscript::<String, Segwitv0>::from_str("and_v(v:pk(A),pk(B))")
+                .unwrap();
+        test_object_encoding_roundtrip(&ms).unwrap();
+
+        // Replacing `and_v` with `and_b` produces miniscript failing type check
+        let mut data = ms.strict_serialize().unwrap();
+        assert_eq!(data[0], super::MS_AND_V);
+        data[0] = super::MS_AND_B;
+        assert!(matches!(
+            Miniscript::<String, Segwitv0>::strict_deserialize(data),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
 }
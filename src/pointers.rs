@@ -18,7 +18,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use crate::{Error, StrictDecode, StrictEncode};
+use crate::{Error, StrictDecode, StrictEncode, StrictSize};
 
 impl StrictEncode for &[u8] {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -162,6 +162,17 @@ where
     }
 }
 
+/// References are encoded in the same way as the values they point to
+impl<T> StrictEncode for &T
+where
+    T: StrictEncode,
+{
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        (*self).strict_encode(e)
+    }
+}
+
 impl StrictEncode for &str {
     fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
         self.as_bytes().strict_encode(e)
@@ -180,9 +191,97 @@ impl StrictDecode for String {
     }
 }
 
+impl StrictSize for &[u8] {
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.len().strict_size() + self.len()
+    }
+}
+
+impl StrictSize for Box<[u8]> {
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.deref().strict_size()
+    }
+}
+
+impl StrictSize for [u8; 16] {
+    const LEN: Option<usize> = Some(16);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        16
+    }
+}
+
+impl StrictSize for [u8; 20] {
+    const LEN: Option<usize> = Some(20);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        20
+    }
+}
+
+impl StrictSize for [u8; 32] {
+    const LEN: Option<usize> = Some(32);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        32
+    }
+}
+
+impl StrictSize for [u8; 64] {
+    const LEN: Option<usize> = Some(64);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        64
+    }
+}
+
+impl<T> StrictSize for Rc<T>
+where
+    T: StrictSize,
+{
+    const LEN: Option<usize> = T::LEN;
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.deref().strict_size()
+    }
+}
+
+impl<T> StrictSize for Arc<T>
+where
+    T: StrictSize,
+{
+    const LEN: Option<usize> = T::LEN;
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.deref().strict_size()
+    }
+}
+
+impl StrictSize for &str {
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.as_bytes().strict_size()
+    }
+}
+
+impl StrictSize for String {
+    #[inline]
+    fn strict_size(&self) -> usize {
+        self.as_bytes().strict_size()
+    }
+}
+
 #[cfg(test)]
 pub mod test {
-    use crate::{strict_deserialize, strict_serialize};
+    use crate::{strict_deserialize, strict_serialize, StrictSize};
 
     fn gen_strings() -> Vec<&'static str> {
         vec![
@@ -224,4 +323,15 @@ pub mod test {
             assert!(p.is_err());
         })
     }
+
+    #[test]
+    fn test_strict_size() {
+        gen_strings().into_iter().for_each(|s| {
+            let len = strict_serialize(&s).unwrap().len();
+            assert_eq!(s.strict_size(), len);
+            assert_eq!(s.to_string().strict_size(), len);
+            assert_eq!(s.as_bytes().strict_size(), len);
+        });
+        assert_eq!([0xA5u8; 32].strict_size(), 32);
+    }
 }
@@ -16,8 +16,9 @@
 
 use core::time::Duration;
 use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::{Error, StrictDecode, StrictEncode};
+use super::{Error, StrictDecode, StrictEncode, StrictSize};
 
 impl StrictEncode for bool {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
@@ -217,9 +218,92 @@ impl StrictDecode for usize {
     }
 }
 
+macro_rules! impl_strict_size_fixed {
+    ($($ty:ty),+) => {
+        $(
+            impl StrictSize for $ty {
+                const LEN: Option<usize> = Some(core::mem::size_of::<$ty>());
+
+                #[inline]
+                fn strict_size(&self) -> usize {
+                    core::mem::size_of::<$ty>()
+                }
+            }
+        )+
+    };
+}
+
+impl_strict_size_fixed!(
+    bool, u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, f32, f64
+);
+
+impl StrictSize for usize {
+    const LEN: Option<usize> = Some(2);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        // `usize` is always encoded as `u16`
+        2
+    }
+}
+
+/// Encodes unsigned integer as a LEB128 variable-length integer (7 bits per
+/// byte, least significant group first, with the highest bit of each byte
+/// signalling continuation). Returns number of bytes written.
+pub(crate) fn leb128_encode<E: io::Write>(
+    mut value: u64,
+    mut e: E,
+) -> Result<usize, Error> {
+    let mut len = 0usize;
+    loop {
+        let mut byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        len += byte.strict_encode(&mut e)?;
+        if value == 0 {
+            return Ok(len);
+        }
+    }
+}
+
+/// Decodes LEB128 variable-length integer, failing on non-canonical encodings
+/// (i.e. containing redundant trailing zero groups) or encodings of values
+/// not fitting into `u64`.
+pub(crate) fn leb128_decode<D: io::Read>(mut d: D) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for pos in 0..10 {
+        let byte = u8::strict_decode(&mut d)?;
+        let bits = (byte & 0x7F) as u64;
+        if pos == 9 && bits > 1 {
+            return Err(Error::DataIntegrityError(s!(
+                "LEB128-encoded value overflows 64 bits"
+            )));
+        }
+        value |= bits << (7 * pos);
+        if byte & 0x80 == 0 {
+            if byte == 0 && pos > 0 {
+                return Err(Error::DataIntegrityError(s!(
+                    "non-canonical LEB128 encoding"
+                )));
+            }
+            return Ok(value);
+        }
+    }
+    Err(Error::DataIntegrityError(s!(
+        "LEB128-encoded value overflows 64 bits"
+    )))
+}
+
+/// All NaN values are encoded with the same canonical bit pattern of
+/// [`f32::NAN`], making the encoding deterministic. This means that the
+/// encoding is lossy for NaN payloads (including signalling NaNs), which
+/// are not preserved.
 impl StrictEncode for f32 {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        e.write_all(&self.to_le_bytes())?;
+        let val = if self.is_nan() { f32::NAN } else { *self };
+        e.write_all(&val.to_le_bytes())?;
         Ok(4)
     }
 }
@@ -232,9 +316,14 @@ impl StrictDecode for f32 {
     }
 }
 
+/// All NaN values are encoded with the same canonical bit pattern of
+/// [`f64::NAN`], making the encoding deterministic. This means that the
+/// encoding is lossy for NaN payloads (including signalling NaNs), which
+/// are not preserved.
 impl StrictEncode for f64 {
     fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
-        e.write_all(&self.to_le_bytes())?;
+        let val = if self.is_nan() { f64::NAN } else { *self };
+        e.write_all(&val.to_le_bytes())?;
         Ok(8)
     }
 }
@@ -254,13 +343,66 @@ impl StrictEncode for Duration {
     }
 }
 
+/// Durations with sub-second nanoseconds value exceeding `999_999_999` are
+/// not allowed and result in [`Error::ValueOutOfRange`].
 impl StrictDecode for Duration {
     #[inline]
     fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
-        Ok(Self::new(
-            u64::strict_decode(&mut d)?,
-            u32::strict_decode(&mut d)?,
-        ))
+        let secs = u64::strict_decode(&mut d)?;
+        let nanos = u32::strict_decode(&mut d)?;
+        if nanos >= 1_000_000_000 {
+            return Err(Error::ValueOutOfRange(
+                "duration nanos",
+                0..1_000_000_000,
+                nanos as u128,
+            ));
+        }
+        Ok(Self::new(secs, nanos))
+    }
+}
+
+/// System time is encoded as a [`Duration`] since [`UNIX_EPOCH`]. Times
+/// preceding the epoch can't be encoded and result in
+/// [`Error::DataIntegrityError`].
+impl StrictEncode for SystemTime {
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        self.duration_since(UNIX_EPOCH)
+            .map_err(|_| {
+                Error::DataIntegrityError(s!("system time precedes UNIX epoch"))
+            })?
+            .strict_encode(e)
+    }
+}
+
+impl StrictDecode for SystemTime {
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        let duration = Duration::strict_decode(d)?;
+        UNIX_EPOCH.checked_add(duration).ok_or_else(|| {
+            Error::DataIntegrityError(s!(
+                "system time is not representable on this platform"
+            ))
+        })
+    }
+}
+
+impl StrictSize for Duration {
+    const LEN: Option<usize> = Some(12);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        // `u64` seconds followed by `u32` nanoseconds
+        12
+    }
+}
+
+impl StrictSize for SystemTime {
+    const LEN: Option<usize> = Some(12);
+
+    #[inline]
+    fn strict_size(&self) -> usize {
+        12
     }
 }
 
@@ -269,20 +411,60 @@ mod _chrono {
     use super::*;
     use chrono::{DateTime, NaiveDateTime, Utc};
 
+    /// Date and time is encoded as a 8-byte signed UNIX timestamp followed by
+    /// 4-byte sub-second nanoseconds.
+    ///
+    /// NB: Prior to v2.0.0 the nanoseconds were not encoded and were dropped
+    /// during the encoding; thus the data encoded with the earlier versions
+    /// can't be decoded with this implementation (and vice versa).
     impl StrictEncode for NaiveDateTime {
         #[inline]
         fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
-            self.timestamp().strict_encode(e)
+            (self.timestamp(), self.timestamp_subsec_nanos()).strict_encode(e)
         }
     }
 
+    /// Date and time is decoded from a 8-byte signed UNIX timestamp followed
+    /// by 4-byte sub-second nanoseconds (see the note on the encoding
+    /// compatibility for the [`StrictEncode`] implementation).
+    ///
+    /// Timestamps (including negative ones) not representable by
+    /// [`NaiveDateTime`] result in [`Error::ValueOutOfRange`]. Since the error
+    /// holds unsigned values, both the timestamp and the supported range are
+    /// reported shifted by 2^63, such that `i64::MIN` corresponds to zero and
+    /// the UNIX epoch to 2^63. Invalid nanosecond values result in
+    /// [`Error::DataIntegrityError`].
     impl StrictDecode for NaiveDateTime {
         #[inline]
-        fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
-            Ok(Self::from_timestamp(i64::strict_decode(d)?, 0))
+        fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+            let ts = i64::strict_decode(&mut d)?;
+            let nanos = u32::strict_decode(&mut d)?;
+            let range = timestamp_to_unsigned(NaiveDateTime::MIN.timestamp())
+                ..timestamp_to_unsigned(NaiveDateTime::MAX.timestamp()) + 1;
+            if !range.contains(&timestamp_to_unsigned(ts)) {
+                return Err(Error::ValueOutOfRange(
+                    "NaiveDateTime",
+                    range,
+                    timestamp_to_unsigned(ts),
+                ));
+            }
+            Self::from_timestamp_opt(ts, nanos).ok_or_else(|| {
+                Error::DataIntegrityError(format!(
+                    "invalid NaiveDateTime nanoseconds value {}",
+                    nanos
+                ))
+            })
         }
     }
 
+    /// Maps signed UNIX timestamp into unsigned value used for reporting
+    /// [`Error::ValueOutOfRange`], preserving the order of the timestamps:
+    /// `i64::MIN` is mapped to zero, zero (UNIX epoch) to 2^63 and `i64::MAX`
+    /// to `u64::MAX`.
+    pub(crate) fn timestamp_to_unsigned(ts: i64) -> u128 {
+        (ts as i128 - i64::MIN as i128) as u128
+    }
+
     impl StrictEncode for DateTime<Utc> {
         #[inline]
         fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
@@ -301,11 +483,34 @@ mod _chrono {
 
 #[cfg(test)]
 pub mod test {
+    use super::_chrono::timestamp_to_unsigned;
     use super::*;
     use crate::strict_deserialize;
     use crate::test_helpers::test_encoding_roundtrip;
     use chrono::{NaiveDateTime, Utc};
 
+    #[test]
+    fn test_strict_size() {
+        fn assert_size<T: StrictSize>(val: T) {
+            let len = val.strict_serialize().unwrap().len();
+            assert_eq!(val.strict_size(), len);
+            assert_eq!(T::LEN, Some(len));
+        }
+        assert_size(true);
+        assert_size(0xFF_u8);
+        assert_size(-1_i8);
+        assert_size(0xFFFF_u16);
+        assert_size(0xFFFF_FFFF_u32);
+        assert_size(-1_i64);
+        assert_size(u128::MAX);
+        assert_size(i128::MIN);
+        assert_size(0xFFFF_usize);
+        assert_size(1.5_f32);
+        assert_size(f64::NAN);
+        assert_size(Duration::new(1, 999_999_999));
+        assert_size(UNIX_EPOCH + Duration::from_secs(1));
+    }
+
     #[test]
     fn test_u_encoding() {
         test_encoding_roundtrip(&0_u8, [0]).unwrap();
@@ -460,20 +665,84 @@ pub mod test {
         .unwrap();
     }
 
+    #[test]
+    fn test_float_nan_encoding() {
+        let nan1 = f32::from_bits(0x7fc0_0001);
+        let nan2 = f32::from_bits(0xffa0_0000);
+        assert!(nan1.is_nan() && nan2.is_nan());
+        assert_ne!(nan1.to_bits(), nan2.to_bits());
+        assert_eq!(
+            nan1.strict_serialize().unwrap(),
+            nan2.strict_serialize().unwrap()
+        );
+        assert_eq!(
+            nan1.strict_serialize().unwrap(),
+            f32::NAN.to_le_bytes().to_vec()
+        );
+
+        let nan1 = f64::from_bits(0x7ff8_0000_0000_0001);
+        let nan2 = f64::from_bits(0xfff4_0000_0000_0000);
+        assert!(nan1.is_nan() && nan2.is_nan());
+        assert_ne!(nan1.to_bits(), nan2.to_bits());
+        assert_eq!(
+            nan1.strict_serialize().unwrap(),
+            nan2.strict_serialize().unwrap()
+        );
+        assert_eq!(
+            nan1.strict_serialize().unwrap(),
+            f64::NAN.to_le_bytes().to_vec()
+        );
+    }
+
+    #[test]
+    fn test_duration_nanos_out_of_range() {
+        let mut data = u64::MAX.to_le_bytes().to_vec();
+        data.extend(&2_000_000_000_u32.to_le_bytes());
+        assert_eq!(
+            Duration::strict_deserialize(&data),
+            Err(Error::ValueOutOfRange(
+                "duration nanos",
+                0..1_000_000_000,
+                2_000_000_000
+            ))
+        );
+    }
+
+    #[test]
+    fn test_system_time_encoding() {
+        let now = SystemTime::now();
+        let ser = now.strict_serialize().unwrap();
+        assert_eq!(ser.len(), 12);
+        assert_eq!(strict_deserialize(&ser), Ok(now));
+
+        test_encoding_roundtrip(
+            &(UNIX_EPOCH + Duration::new(0x5f5e100, 123_456_789)),
+            [
+                0x00, 0xe1, 0xf5, 0x05, 0x00, 0x00, 0x00, 0x00, 0x15, 0xcd,
+                0x5b, 0x07,
+            ],
+        )
+        .unwrap();
+
+        assert!((UNIX_EPOCH - Duration::from_secs(1))
+            .strict_serialize()
+            .is_err());
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn test_chrono_encoding() {
         let utc = Utc::now();
 
         let ser = utc.strict_serialize().unwrap();
-        assert_eq!(ser.len(), 8);
+        assert_eq!(ser.len(), 12);
 
         let naive = utc.naive_utc();
-        let naive = NaiveDateTime::from_timestamp(naive.timestamp(), 0);
         assert_eq!(strict_deserialize(&ser), Ok(naive));
+        assert_eq!(strict_deserialize(&ser), Ok(utc));
 
         let ser = naive.strict_serialize().unwrap();
-        assert_eq!(ser.len(), 8);
+        assert_eq!(ser.len(), 12);
         assert_eq!(strict_deserialize(&ser), Ok(naive));
 
         let duration = Duration::new(naive.timestamp() as u64, 38455567);
@@ -481,4 +750,57 @@ pub mod test {
         assert_eq!(ser.len(), 12);
         assert_eq!(strict_deserialize(&ser), Ok(duration));
     }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_chrono_nanos() {
+        let naive = NaiveDateTime::from_timestamp(0x5f5e100, 123_456_789);
+        test_encoding_roundtrip(
+            &naive,
+            [
+                0x00, 0xe1, 0xf5, 0x05, 0x00, 0x00, 0x00, 0x00, 0x15, 0xcd,
+                0x5b, 0x07,
+            ],
+        )
+        .unwrap();
+        assert_eq!(naive.timestamp_subsec_nanos(), 123_456_789);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_chrono_out_of_range() {
+        // Dates before the UNIX epoch are valid
+        let naive = NaiveDateTime::from_timestamp(-1_000_000_000, 5);
+        let data = naive.strict_serialize().unwrap();
+        assert_eq!(data[..8], (-1_000_000_000i64).to_le_bytes());
+        assert_eq!(NaiveDateTime::strict_deserialize(&data), Ok(naive));
+
+        assert_eq!(timestamp_to_unsigned(i64::MIN), 0);
+        assert_eq!(timestamp_to_unsigned(0), 1 << 63);
+        assert_eq!(timestamp_to_unsigned(i64::MAX), u64::MAX as u128);
+
+        let range = timestamp_to_unsigned(NaiveDateTime::MIN.timestamp())
+            ..timestamp_to_unsigned(NaiveDateTime::MAX.timestamp()) + 1;
+        for ts in &[i64::MAX, i64::MIN] {
+            let mut data = ts.to_le_bytes().to_vec();
+            data.extend(&[0u8; 4]);
+            assert_eq!(
+                NaiveDateTime::strict_deserialize(&data),
+                Err(Error::ValueOutOfRange(
+                    "NaiveDateTime",
+                    range.clone(),
+                    timestamp_to_unsigned(*ts)
+                ))
+            );
+        }
+
+        let mut data = 0i64.to_le_bytes().to_vec();
+        data.extend(&2_000_000_000u32.to_le_bytes());
+        assert_eq!(
+            NaiveDateTime::strict_deserialize(&data),
+            Err(Error::DataIntegrityError(s!(
+                "invalid NaiveDateTime nanoseconds value 2000000000"
+            )))
+        );
+    }
 }
@@ -0,0 +1,168 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Helpers for embedding strict-encoded data into serde-serialized documents.
+//! The data are represented as a hex string in human-readable formats (like
+//! JSON or YAML) and as a byte string in binary formats (like CBOR).
+//!
+//! The module is intended to be used with serde `with` attribute:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Document {
+//!     #[serde(with = "strict_encoding::serde_helpers")]
+//!     consignment: Consignment,
+//! }
+//! ```
+
+use std::fmt;
+
+use bitcoin_hashes::hex::{FromHex, ToHex};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserializer, Serializer};
+
+use crate::{strict_deserialize, strict_serialize, StrictDecode, StrictEncode};
+
+pub use self::deserialize_strict as deserialize;
+pub use self::serialize_strict as serialize;
+
+/// Serializes strict encoding of the value as a hex string for human-readable
+/// formats and as a byte string otherwise
+pub fn serialize_strict<T, S>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: StrictEncode,
+    S: Serializer,
+{
+    let data = strict_serialize(value).map_err(serde::ser::Error::custom)?;
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&data.to_hex())
+    } else {
+        serializer.serialize_bytes(&data)
+    }
+}
+
+/// Deserializes value from its strict encoding, represented as a hex string
+/// for human-readable formats and as a byte string otherwise. The strict
+/// encoding must be consumed entirely during decoding.
+pub fn deserialize_strict<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: StrictDecode,
+    D: Deserializer<'de>,
+{
+    let data = if deserializer.is_human_readable() {
+        deserializer.deserialize_str(HexVisitor)?
+    } else {
+        deserializer.deserialize_bytes(BytesVisitor)?
+    };
+    strict_deserialize(data).map_err(de::Error::custom)
+}
+
+struct HexVisitor;
+
+impl<'de> Visitor<'de> for HexVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("hex string with strict-encoded data")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Vec::<u8>::from_hex(v).map_err(E::custom)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("byte string with strict-encoded data")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(
+        self,
+        v: Vec<u8>,
+    ) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            data.push(byte);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct Record {
+        id: u16,
+        tags: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    struct Document {
+        title: String,
+        #[serde(with = "crate::serde_helpers")]
+        record: Record,
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let doc = Document {
+            title: s!("strict"),
+            record: Record {
+                id: 0x0102,
+                tags: vec![s!("a")],
+            },
+        };
+
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "title": "strict",
+                "record": "02010100010061",
+            })
+        );
+        assert_eq!(serde_json::from_value::<Document>(value).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        let value = serde_json::json!({ "title": "", "record": "0201" });
+        assert!(serde_json::from_value::<Document>(value).is_err());
+        let value = serde_json::json!({ "title": "", "record": "xx" });
+        assert!(serde_json::from_value::<Document>(value).is_err());
+    }
+}
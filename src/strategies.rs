@@ -17,15 +17,22 @@
 //! Implemented after concept by Martin Habovštiak <martin.habovstiak@gmail.com>
 
 use amplify::Wrapper;
+use std::convert::TryFrom;
 use std::io;
 
 use super::net;
+use super::primitives::{leb128_decode, leb128_encode};
 use super::{Error, StrictDecode, StrictEncode};
 
 /// Encodes/decodes data as a [`bitcoin_hashes::Hash`]-based (wrapper) type,
 /// i.e. as a fixed-size byte string of [`bitcoin_hashes::Hash::LEN`] length.
 pub struct HashFixedBytes;
 
+/// Encodes/decodes data as a [`bitcoin_hashes::Hash`]-based (wrapper) type
+/// in the same way as [`HashFixedBytes`], but with the reversed byte order,
+/// matching the order used for the display of the hash.
+pub struct HashReversed;
+
 /// Encodes/decodes data in the same way as they are encoded/decoded according
 /// to bitcoin consensus rules from Bitcoin Core
 #[cfg(feature = "bitcoin")]
@@ -40,14 +47,51 @@ pub struct Wrapped;
 /// encoding" rules. Applicable only for types implementing [`net::Uniform`].
 pub struct UsingUniformAddr;
 
+/// Encodes/decodes unsigned integer wrapped by a type as a variable-length
+/// LEB128 integer (7 bits per byte, with the highest bit indicating presence
+/// of the next byte). Applicable only for types implementing
+/// [`amplify::Wrapper`] with `u8`, `u16`, `u32` or `u64` inner type.
+///
+/// Decoding fails with [`Error::DataIntegrityError`] on non-canonical
+/// encodings (i.e. the ones which use more bytes than required for the value)
+/// and on values not fitting into the inner type.
+pub struct UsingUleb128;
+
+/// Encodes/decodes fieldless enum as a single byte of its discriminant.
+/// Applicable only for types implementing both `TryFrom<u8>` and `Into<u8>`,
+/// and thus supports enums with up to 256 variants. Decoding of a byte not
+/// corresponding to any of the enum variants fails with
+/// [`Error::EnumValueNotKnown`], which reports the full type name of the enum
+/// (as given by [`core::any::type_name`]).
+pub struct EnumU8;
+
+/// Encodes/decodes `Vec` wrapped by a type with a 32-bit length prefix instead
+/// of the 16-bit one used by the default `Vec` encoding, raising the limit on
+/// the number of items from 65,535 to 4,294,967,295. Applicable only for types
+/// implementing [`amplify::Wrapper`] with `Vec<T>` inner type.
+///
+/// NB: the encoding is not compatible with the default one: data encoded with
+///     this strategy can't be decoded as a `Vec` and vice versa, so the
+///     strategy must not be applied to the types which are already a part of
+///     some existing (consensus, network or storage) format.
+pub struct LargeCollection;
+
+/// Maximum number of bytes preallocated for the items of [`LargeCollection`]
+/// before they are decoded
+const MAX_PREALLOC_BYTES: usize = 1 << 20;
+
 /// Marker trait defining specific encoding strategy which should be used for
 /// automatic implementation of both [`StrictEncode`] and [`StrictDecode`].
 pub trait Strategy {
     /// Specific strategy. List of supported strategies:
     /// - [`HashFixedBytes`]
+    /// - [`HashReversed`]
     /// - [`BitcoinConsensus`]
     /// - [`Wrapped`]
     /// - [`UsingUniformAddr`]
+    /// - [`UsingUleb128`]
+    /// - [`EnumU8`]
+    /// - [`LargeCollection`]
     type Strategy;
 }
 
@@ -121,6 +165,35 @@ where
     }
 }
 
+impl<H> StrictEncode for amplify::Holder<H, HashReversed>
+where
+    H: bitcoin_hashes::Hash,
+{
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let mut buf = self.as_inner()[..].to_vec();
+        buf.reverse();
+        e.write_all(&buf)?;
+        Ok(H::LEN)
+    }
+}
+
+impl<H> StrictDecode for amplify::Holder<H, HashReversed>
+where
+    H: bitcoin_hashes::Hash,
+{
+    #[inline]
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let mut buf = vec![0u8; H::LEN];
+        d.read_exact(&mut buf)?;
+        buf.reverse();
+        Ok(Self::new(H::from_slice(&buf).expect(
+            "internal hash data representation length mismatch between \
+            `from_slice` requirements and `LEN` constant value",
+        )))
+    }
+}
+
 #[cfg(feature = "bitcoin")]
 impl<B> StrictEncode for amplify::Holder<B, BitcoinConsensus>
 where
@@ -139,7 +212,22 @@ where
 {
     #[inline]
     fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
-        Ok(Self::new(B::consensus_decode(d).map_err(Error::from)?))
+        Ok(Self::new(BitcoinConsensus::decode_consuming(d)?.0))
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+impl BitcoinConsensus {
+    /// Decodes value according to bitcoin consensus rules, returning it
+    /// together with the number of bytes consumed from the reader.
+    pub fn decode_consuming<T, D>(d: D) -> Result<(T, usize), Error>
+    where
+        T: bitcoin::consensus::Decodable,
+        D: io::Read,
+    {
+        let mut reader = crate::StrictReader::new(d);
+        let val = T::consensus_decode(&mut reader).map_err(Error::from)?;
+        Ok((val, reader.position()))
     }
 }
 
@@ -166,6 +254,100 @@ where
     }
 }
 
+impl<W> StrictEncode for amplify::Holder<W, UsingUleb128>
+where
+    W: Wrapper,
+    W::Inner: Into<u64>,
+{
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        leb128_encode(self.as_inner().to_inner().into(), e)
+    }
+}
+
+impl<W> StrictDecode for amplify::Holder<W, UsingUleb128>
+where
+    W: Wrapper,
+    W::Inner: TryFrom<u64>,
+{
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        let value = leb128_decode(d)?;
+        let inner = W::Inner::try_from(value).map_err(|_| {
+            Error::DataIntegrityError(format!(
+                "LEB128-encoded value {} exceeds {}-bit integer",
+                value,
+                std::mem::size_of::<W::Inner>() * 8
+            ))
+        })?;
+        Ok(Self::new(W::from_inner(inner)))
+    }
+}
+
+impl<T> StrictEncode for amplify::Holder<T, EnumU8>
+where
+    T: Clone + Into<u8>,
+{
+    #[inline]
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, Error> {
+        let discriminant: u8 = self.as_inner().clone().into();
+        discriminant.strict_encode(e)
+    }
+}
+
+impl<T> StrictDecode for amplify::Holder<T, EnumU8>
+where
+    T: TryFrom<u8>,
+{
+    #[inline]
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, Error> {
+        let discriminant = u8::strict_decode(d)?;
+        T::try_from(discriminant).map(Self::new).map_err(|_| {
+            Error::EnumValueNotKnown(
+                core::any::type_name::<T>(),
+                discriminant as usize,
+            )
+        })
+    }
+}
+
+impl<W, T> StrictEncode for amplify::Holder<W, LargeCollection>
+where
+    W: Wrapper<Inner = Vec<T>>,
+    T: StrictEncode,
+{
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, Error> {
+        let items = self.as_inner().as_inner();
+        let len = u32::try_from(items.len())
+            .map_err(|_| Error::ExceedMaxItems(items.len()))?;
+        let mut encoded = len.strict_encode(&mut e)?;
+        for item in items {
+            encoded += item.strict_encode(&mut e)?;
+        }
+        Ok(encoded)
+    }
+}
+
+impl<W, T> StrictDecode for amplify::Holder<W, LargeCollection>
+where
+    W: Wrapper<Inner = Vec<T>>,
+    T: StrictDecode,
+{
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, Error> {
+        let len = u32::strict_decode(&mut d)? as usize;
+        // Do not trust the length prefix with the memory allocation: it may
+        // be up to 4 billions of items coming from malformed data, so the
+        // preallocated memory is limited in bytes, and the vector grows
+        // further only as the items are actually decoded
+        let max_prealloc =
+            MAX_PREALLOC_BYTES / core::mem::size_of::<T>().max(1);
+        let mut items = Vec::with_capacity(len.min(max_prealloc));
+        for _ in 0..len {
+            items.push(T::strict_decode(&mut d)?);
+        }
+        Ok(Self::new(W::from_inner(items)))
+    }
+}
+
 #[cfg(feature = "bitcoin")]
 impl From<bitcoin::hashes::Error> for Error {
     #[inline]
@@ -185,3 +367,180 @@ impl From<bitcoin::consensus::encode::Error> for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bitcoin_hashes::{sha256, Hash};
+
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Side {
+        Buy = 1,
+        Sell = 2,
+        Cancel = 0xFF,
+    }
+
+    impl Strategy for Side {
+        type Strategy = EnumU8;
+    }
+
+    impl From<Side> for u8 {
+        fn from(side: Side) -> Self {
+            side as u8
+        }
+    }
+
+    impl TryFrom<u8> for Side {
+        type Error = u8;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                1 => Ok(Side::Buy),
+                2 => Ok(Side::Sell),
+                0xFF => Ok(Side::Cancel),
+                unknown => Err(unknown),
+            }
+        }
+    }
+
+    #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+    struct Height(u64);
+
+    impl Strategy for Height {
+        type Strategy = Wrapped;
+    }
+
+    #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+    struct Count(u16);
+
+    impl Strategy for Count {
+        type Strategy = UsingUleb128;
+    }
+
+    #[derive(Wrapper, Clone, Copy, PartialEq, Eq, Debug, From)]
+    struct Amount(u64);
+
+    impl Strategy for Amount {
+        type Strategy = UsingUleb128;
+    }
+
+    #[derive(Wrapper, Clone, PartialEq, Eq, Debug, Default, From)]
+    struct Transitions(Vec<u8>);
+
+    impl Strategy for Transitions {
+        type Strategy = LargeCollection;
+    }
+
+    #[test]
+    fn test_large_collection() {
+        let small = Transitions(vec![0xA5; 3]);
+        let data = small.strict_serialize().unwrap();
+        assert_eq!(data, vec![0x03, 0x00, 0x00, 0x00, 0xA5, 0xA5, 0xA5]);
+        assert_eq!(Transitions::strict_deserialize(data).unwrap(), small);
+
+        let items = vec![0x5A; u16::MAX as usize + 1];
+        assert_eq!(
+            items.strict_serialize().unwrap_err(),
+            Error::ExceedMaxItems(u16::MAX as usize + 1)
+        );
+
+        let large = Transitions(items);
+        let data = large.strict_serialize().unwrap();
+        assert_eq!(data.len(), 4 + u16::MAX as usize + 1);
+        assert_eq!(data[..4], [0x00, 0x00, 0x01, 0x00]);
+        assert_eq!(Transitions::strict_deserialize(data).unwrap(), large);
+
+        // Truncated data must not be decoded
+        assert!(Transitions::strict_deserialize(&[
+            0xFF, 0xFF, 0xFF, 0xFF, 0x00
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_hash_reversed() {
+        let hash = sha256::Hash::hash(b"strict encoding");
+        let mut data = hash.strict_serialize().unwrap();
+        data.reverse();
+
+        let reversed = amplify::Holder::<_, HashReversed>::new(hash);
+        assert_eq!(reversed.strict_serialize().unwrap(), data);
+        assert_eq!(
+            amplify::Holder::<sha256::Hash, HashReversed>::strict_deserialize(
+                &data
+            )
+            .unwrap()
+            .into_inner(),
+            hash
+        );
+    }
+
+    #[test]
+    fn test_enum_u8() {
+        for (side, byte) in
+            &[(Side::Buy, 1u8), (Side::Sell, 2), (Side::Cancel, 0xFF)]
+        {
+            assert_eq!(side.strict_serialize().unwrap(), vec![*byte]);
+            assert_eq!(Side::strict_deserialize(&[*byte]).unwrap(), *side);
+        }
+        assert_eq!(
+            Side::strict_deserialize(&[3u8]).unwrap_err(),
+            Error::EnumValueNotKnown(core::any::type_name::<Side>(), 3)
+        );
+    }
+
+    #[test]
+    fn test_wrapped() {
+        let height = Height(0x0102_0304_0506_0708);
+        let data = height.strict_serialize().unwrap();
+        assert_eq!(data, 0x0102_0304_0506_0708u64.strict_serialize().unwrap());
+        assert_eq!(Height::strict_deserialize(data).unwrap(), height);
+        assert!(Height::strict_deserialize(&[0u8; 7]).is_err());
+    }
+
+    #[test]
+    fn test_uleb128() {
+        let cases: [(u16, &[u8]); 6] = [
+            (0, &[0x00]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (16383, &[0xFF, 0x7F]),
+            (16384, &[0x80, 0x80, 0x01]),
+            (u16::MAX, &[0xFF, 0xFF, 0x03]),
+        ];
+        for (value, bytes) in &cases {
+            let count = Count(*value);
+            assert_eq!(count.strict_serialize().unwrap(), *bytes);
+            assert_eq!(Count::strict_deserialize(bytes).unwrap(), count);
+        }
+
+        let amount = Amount(u64::MAX);
+        let data = amount.strict_serialize().unwrap();
+        assert_eq!(data.len(), 10);
+        assert_eq!(Amount::strict_deserialize(data).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_uleb128_non_canonical() {
+        // Over-long encodings of 0 and 127
+        assert!(matches!(
+            Count::strict_deserialize(&[0x80, 0x00]),
+            Err(Error::DataIntegrityError(_))
+        ));
+        assert!(matches!(
+            Count::strict_deserialize(&[0xFF, 0x80, 0x00]),
+            Err(Error::DataIntegrityError(_))
+        ));
+        // `u16::MAX + 1` does not fit into `u16`
+        assert!(matches!(
+            Count::strict_deserialize(&[0x80, 0x80, 0x04]),
+            Err(Error::DataIntegrityError(_))
+        ));
+        // Four bytes are more than required for any 16-bit value
+        assert!(matches!(
+            Count::strict_deserialize(&[0x80, 0x80, 0x80, 0x01]),
+            Err(Error::DataIntegrityError(_))
+        ));
+    }
+}
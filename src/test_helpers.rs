@@ -70,7 +70,7 @@
 
 use std::fmt::Debug;
 
-use crate::{Error, StrictDecode, StrictEncode};
+use crate::{Error, StrictDecode, StrictEncode, StrictWriter};
 
 /// Failures happening during strict encoding tests of enum encodings.
 ///
@@ -557,11 +557,12 @@ pub fn test_object_encoding_roundtrip<T>(
 where
     T: StrictEncode + StrictDecode + PartialEq + Clone + Debug,
 {
-    let mut encoded_object: Vec<u8> = vec![];
+    let mut writer = StrictWriter::new(vec![]);
     let written = object
-        .strict_encode(&mut encoded_object)
+        .strict_encode(&mut writer)
         .map_err(DataEncodingTestFailure::EncoderFailure)?;
-    let len = encoded_object.len();
+    let len = writer.position();
+    let encoded_object = writer.into_inner();
     if written != len {
         return Err(DataEncodingTestFailure::EncoderReturnedWrongLength {
             actual: len,
@@ -582,5 +583,3 @@ where
     }
     Ok(encoded_object)
 }
-
-
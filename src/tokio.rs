@@ -0,0 +1,384 @@
+// LNP/BP client-side-validation foundation libraries implementing LNPBP
+// specifications & standards (LNPBP-4, 7, 8, 9, 42, 81)
+//
+// Written in 2019-2021 by
+//     Dr. Maxim Orlovsky <orlovsky@pandoracore.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the Apache 2.0 License along with this
+// software. If not, see <https://opensource.org/licenses/Apache-2.0>.
+
+//! Asynchronous counterparts of strict encoding traits operating on `tokio`
+//! readers and writers. The produced encoding is identical to the one of
+//! [`StrictEncode`] and [`StrictDecode`] traits.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{Error, StrictDecode, StrictEncode};
+
+/// Boxed future returned by asynchronous strict encoding operations
+pub type StrictFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// Asynchronous version of [`StrictEncode`]. Implemented for all types
+/// implementing [`StrictEncode`]: the data are strict-encoded into an
+/// intermediary buffer, which is then written to the asynchronous writer.
+pub trait AsyncStrictEncode {
+    /// Encodes data into the asynchronous writer, returning the number of
+    /// bytes written
+    fn async_strict_encode<'a, W>(
+        &'a self,
+        w: &'a mut W,
+    ) -> StrictFuture<'a, usize>
+    where
+        W: AsyncWrite + Unpin + Send + 'a;
+}
+
+impl<T> AsyncStrictEncode for T
+where
+    T: StrictEncode + Sync,
+{
+    fn async_strict_encode<'a, W>(
+        &'a self,
+        w: &'a mut W,
+    ) -> StrictFuture<'a, usize>
+    where
+        W: AsyncWrite + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let data = self.strict_serialize()?;
+            w.write_all(&data).await?;
+            Ok(data.len())
+        })
+    }
+}
+
+/// Asynchronous version of [`StrictDecode`]. Since the size of the encoded
+/// data is not known in advance, the trait can't be derived from the
+/// synchronous implementation and must be implemented for each of the types
+/// (the library provides implementations for primitive types, byte arrays,
+/// strings, tuples, `Option`, `Vec` and the set and map collections which
+/// have [`StrictDecode`] implementations).
+pub trait AsyncStrictDecode: Sized + Send {
+    /// Decodes data from the asynchronous reader
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a;
+}
+
+macro_rules! impl_async_decode_fixed {
+    ($($ty:ty => $len:expr),+ $(,)?) => {
+        $(
+            impl AsyncStrictDecode for $ty {
+                fn async_strict_decode<'a, R>(
+                    r: &'a mut R,
+                ) -> StrictFuture<'a, Self>
+                where
+                    R: AsyncRead + Unpin + Send + 'a,
+                {
+                    Box::pin(async move {
+                        let mut buf = [0u8; $len];
+                        r.read_exact(&mut buf).await?;
+                        <$ty>::strict_decode(&buf[..])
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_async_decode_fixed!(
+    bool => 1,
+    u8 => 1,
+    i8 => 1,
+    u16 => 2,
+    i16 => 2,
+    u32 => 4,
+    i32 => 4,
+    u64 => 8,
+    i64 => 8,
+    u128 => 16,
+    i128 => 16,
+    usize => 2,
+    f32 => 4,
+    f64 => 8,
+    [u8; 16] => 16,
+    [u8; 20] => 20,
+    [u8; 32] => 32,
+    [u8; 64] => 64,
+);
+
+impl<T> AsyncStrictDecode for Option<T>
+where
+    T: AsyncStrictDecode,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            match u8::async_strict_decode(&mut *r).await? {
+                0 => Ok(None),
+                1 => Ok(Some(T::async_strict_decode(&mut *r).await?)),
+                invalid => Err(Error::WrongOptionalEncoding(invalid)),
+            }
+        })
+    }
+}
+
+impl<T> AsyncStrictDecode for Vec<T>
+where
+    T: AsyncStrictDecode,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let len = usize::async_strict_decode(&mut *r).await?;
+            let mut data = Vec::<T>::with_capacity(len);
+            for _ in 0..len {
+                data.push(T::async_strict_decode(&mut *r).await?);
+            }
+            Ok(data)
+        })
+    }
+}
+
+/// Mirrors [`StrictDecode`] implementation for `HashSet`, failing with
+/// [`Error::RepeatedValue`] if the same value is met twice
+impl<T> AsyncStrictDecode for HashSet<T>
+where
+    T: AsyncStrictDecode + Eq + Ord + Hash + Debug,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let len = usize::async_strict_decode(&mut *r).await?;
+            let mut data = HashSet::<T>::with_capacity(len);
+            for _ in 0..len {
+                let val = T::async_strict_decode(&mut *r).await?;
+                if data.contains(&val) {
+                    return Err(Error::RepeatedValue(format!("{:?}", val)));
+                }
+                data.insert(val);
+            }
+            Ok(data)
+        })
+    }
+}
+
+/// Mirrors [`StrictDecode`] implementation for `BTreeSet`, failing with
+/// [`Error::RepeatedValue`] if the same value is met twice
+impl<T> AsyncStrictDecode for BTreeSet<T>
+where
+    T: AsyncStrictDecode + Eq + Ord + Debug,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let len = usize::async_strict_decode(&mut *r).await?;
+            let mut data = BTreeSet::<T>::new();
+            for _ in 0..len {
+                let val = T::async_strict_decode(&mut *r).await?;
+                if data.contains(&val) {
+                    return Err(Error::RepeatedValue(format!("{:?}", val)));
+                }
+                data.insert(val);
+            }
+            Ok(data)
+        })
+    }
+}
+
+impl<T> AsyncStrictDecode for HashMap<usize, T>
+where
+    T: AsyncStrictDecode,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            Ok(BTreeMap::<usize, T>::async_strict_decode(&mut *r)
+                .await?
+                .into_iter()
+                .collect())
+        })
+    }
+}
+
+impl<K, V> AsyncStrictDecode for BTreeMap<K, V>
+where
+    K: AsyncStrictDecode + Ord,
+    V: AsyncStrictDecode,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let len = usize::async_strict_decode(&mut *r).await?;
+            let mut map = BTreeMap::<K, V>::new();
+            for _ in 0..len {
+                let key = K::async_strict_decode(&mut *r).await?;
+                let val = V::async_strict_decode(&mut *r).await?;
+                map.insert(key, val);
+            }
+            Ok(map)
+        })
+    }
+}
+
+impl<K, V> AsyncStrictDecode for (K, V)
+where
+    K: AsyncStrictDecode,
+    V: AsyncStrictDecode,
+{
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let a = K::async_strict_decode(&mut *r).await?;
+            let b = V::async_strict_decode(&mut *r).await?;
+            Ok((a, b))
+        })
+    }
+}
+
+impl AsyncStrictDecode for String {
+    fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+    where
+        R: AsyncRead + Unpin + Send + 'a,
+    {
+        Box::pin(async move {
+            let data = Vec::<u8>::async_strict_decode(&mut *r).await?;
+            String::from_utf8(data).map_err(Error::from)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+    #[strict_encoding(crate = crate)]
+    struct Message {
+        id: u32,
+        payload: Vec<u8>,
+        note: Option<String>,
+    }
+
+    impl AsyncStrictDecode for Message {
+        fn async_strict_decode<'a, R>(r: &'a mut R) -> StrictFuture<'a, Self>
+        where
+            R: AsyncRead + Unpin + Send + 'a,
+        {
+            Box::pin(async move {
+                Ok(Message {
+                    id: u32::async_strict_decode(&mut *r).await?,
+                    payload: Vec::async_strict_decode(&mut *r).await?,
+                    note: Option::async_strict_decode(&mut *r).await?,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplex_roundtrip() {
+        let msg = Message {
+            id: 0xDEAD_BEEF,
+            payload: vec![0xA5; 100],
+            note: Some(s!("async")),
+        };
+
+        // Buffer is smaller than the message, so the writer has to wait for
+        // the reader to consume the data
+        let (mut client, mut server) = duplex(16);
+        let sent = msg.clone();
+        let writer =
+            tokio::spawn(
+                async move { sent.async_strict_encode(&mut client).await },
+            );
+
+        let received = Message::async_strict_decode(&mut server).await.unwrap();
+        let len = writer.await.unwrap().unwrap();
+
+        assert_eq!(received, msg);
+        assert_eq!(len, 4 + 2 + 100 + 1 + 2 + 5);
+        assert_eq!(len, msg.strict_serialize().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_collections() {
+        let set: HashSet<u16> = [1, 2, 3].iter().copied().collect();
+        let tree_set: BTreeSet<u16> = set.iter().copied().collect();
+        let map: HashMap<usize, String> =
+            vec![(0, s!("zero")), (5, s!("five"))].into_iter().collect();
+        let tree_map: BTreeMap<u8, Vec<u8>> =
+            vec![(1, vec![0xA1]), (2, vec![])].into_iter().collect();
+        let tuple = (0x10_u8, s!("tuple"));
+
+        let data = set.strict_serialize().unwrap();
+        assert_eq!(HashSet::async_strict_decode(&mut &data[..]).await, Ok(set));
+        let data = tree_set.strict_serialize().unwrap();
+        assert_eq!(
+            BTreeSet::async_strict_decode(&mut &data[..]).await,
+            Ok(tree_set)
+        );
+        let data = map.strict_serialize().unwrap();
+        assert_eq!(HashMap::async_strict_decode(&mut &data[..]).await, Ok(map));
+        let data = tree_map.strict_serialize().unwrap();
+        assert_eq!(
+            BTreeMap::async_strict_decode(&mut &data[..]).await,
+            Ok(tree_map)
+        );
+        let data = tuple.strict_serialize().unwrap();
+        assert_eq!(
+            <(u8, String)>::async_strict_decode(&mut &data[..]).await,
+            Ok(tuple)
+        );
+
+        // Repeated set values are rejected as in the synchronous decoding
+        let data = [0x02, 0x00, 0x07, 0x00, 0x07, 0x00];
+        assert_eq!(
+            HashSet::<u16>::async_strict_decode(&mut &data[..]).await,
+            Err(Error::RepeatedValue(s!("7")))
+        );
+        assert_eq!(
+            BTreeSet::<u16>::async_strict_decode(&mut &data[..]).await,
+            Err(Error::RepeatedValue(s!("7")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_truncated() {
+        let (mut client, mut server) = duplex(16);
+        client.write_all(&[0x03, 0x00, 0x01]).await.unwrap();
+        drop(client);
+        assert_eq!(
+            Vec::<u8>::async_strict_decode(&mut server)
+                .await
+                .unwrap_err(),
+            Error::UnexpectedEof
+        );
+    }
+}